@@ -0,0 +1,92 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use futures::executor::ThreadPool;
+use rx_rust_mp::create::create;
+use rx_rust_mp::observable::Observable;
+use rx_rust_mp::observer::Observer;
+use rx_rust_mp::subscriber::Subscriber;
+use std::convert::Infallible;
+
+const ITEMS: u64 = 10_000;
+
+fn source() -> impl Observable<Item = u64, Error = Infallible> {
+    create(|sender: Subscriber<u64, Infallible>| {
+        for i in 0..ITEMS {
+            sender.next(i).unwrap();
+        }
+    })
+}
+
+fn map(c: &mut Criterion) {
+    let pool = ThreadPool::new().unwrap();
+    let mut group = c.benchmark_group("map");
+    group.bench_function("spawn per item", |b| {
+        b.iter(|| {
+            let subscription = source().map(|v| v + 1).subscribe(
+                |v| {
+                    black_box(v);
+                },
+                pool.clone(),
+            );
+            futures::executor::block_on(subscription);
+        })
+    });
+    // Compared to the above, shows what moving the subscriber to another scheduler costs
+    let observer = ThreadPool::new().unwrap();
+    group.bench_function("spawn per item, observed on another pool", |b| {
+        b.iter(|| {
+            let subscription = source()
+                .map(|v| v + 1)
+                .observe_on(observer.clone())
+                .subscribe(
+                    |v| {
+                        black_box(v);
+                    },
+                    pool.clone(),
+                );
+            futures::executor::block_on(subscription);
+        })
+    });
+    group.bench_function("inline", |b| {
+        b.iter(|| {
+            let subscription = source().map_inline(|v| v + 1).subscribe(
+                |v| {
+                    black_box(v);
+                },
+                pool.clone(),
+            );
+            futures::executor::block_on(subscription);
+        })
+    });
+    group.finish();
+}
+
+fn filter(c: &mut Criterion) {
+    let pool = ThreadPool::new().unwrap();
+    let mut group = c.benchmark_group("filter");
+    group.bench_function("spawn per item", |b| {
+        b.iter(|| {
+            let subscription = source().filter(|v| v % 2 == 0).subscribe(
+                |v| {
+                    black_box(v);
+                },
+                pool.clone(),
+            );
+            futures::executor::block_on(subscription);
+        })
+    });
+    group.bench_function("inline", |b| {
+        b.iter(|| {
+            let subscription = source().filter_inline(|v| v % 2 == 0).subscribe(
+                |v| {
+                    black_box(v);
+                },
+                pool.clone(),
+            );
+            futures::executor::block_on(subscription);
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, map, filter);
+criterion_main!(benches);
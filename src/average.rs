@@ -1,12 +1,10 @@
+use crate::notification::Notification;
 use crate::observable::Observable;
 use crate::scheduler::Scheduler;
-use log::{error, trace};
+use crate::subscriber::Subscriber;
+use crate::utils;
 use num_traits::Zero;
-use std::io;
-use std::io::ErrorKind;
-use std::ops::{AddAssign, Div};
-use std::sync::mpsc;
-use std::sync::mpsc::Sender;
+use std::ops::{AddAssign, ControlFlow, Div};
 
 pub struct AverageObservable<Source, C, CC> {
     pub(crate) source: Source,
@@ -18,39 +16,45 @@ impl<C, Source, CC> Observable for AverageObservable<Source, C, CC>
 where
     Source: Observable,
     Source::Item: Send + 'static + Zero,
+    Source::Error: Send + 'static,
     C: AddAssign<Source::Item> + Div<Source::Item, Output = Source::Item> + Send + 'static,
     CC: Into<Source::Item> + AddAssign<i32> + Send + 'static,
 {
     type Item = Source::Item;
+    type Error = Source::Error;
 
-    fn actual_subscribe<O>(mut self, channel: Sender<io::Result<Self::Item>>, pool: O)
+    fn actual_subscribe<O>(self, channel: Subscriber<Self::Item, Self::Error>, pool: O)
     where
         O: Scheduler + Clone + Send + 'static,
     {
-        let (incoming_tx, incoming_rx) = mpsc::channel::<io::Result<Source::Item>>();
-        pool.schedule(move || {
-            loop {
-                let message = incoming_rx.recv();
-                match message {
-                    Ok(Ok(message)) => {
-                        self.collector += message;
-                        self.count += 1;
+        let (incoming_tx, incoming_rx) = channel.upstream::<Source::Item, Source::Error>();
+        let mut totals = Some((self.collector, self.count));
+        utils::receive(
+            &pool,
+            incoming_rx,
+            "Average",
+            move |message| match message {
+                Ok(Notification::Next(message)) => {
+                    if let Some((collector, count)) = totals.as_mut() {
+                        *collector += message;
+                        *count += 1;
                     }
-                    Ok(Err(e)) => {
-                        error!("Reduce, inner unwrap: {:?}", e.to_string());
-                        channel
-                            .send(Err(io::Error::new(ErrorKind::Other, e)))
-                            .unwrap();
-                        break;
+                    ControlFlow::Continue(())
+                }
+                Ok(Notification::Error(e)) => {
+                    let _ = channel.send(Notification::Error(e));
+                    ControlFlow::Break(())
+                }
+                Ok(Notification::Complete) => {
+                    if let Some((collector, count)) = totals.take() {
+                        let _ = channel.send(Notification::Next(collector / count.into()));
                     }
-                    Err(_) => break, // Channel closed
+                    let _ = channel.send(Notification::Complete);
+                    ControlFlow::Break(())
                 }
-            }
-            channel
-                .send(Ok(self.collector / self.count.into()))
-                .unwrap();
-            trace!("Average finished");
-        })
+                Err(_) => ControlFlow::Break(()), // Upstream terminated without completing
+            },
+        )
         .forget();
         self.source.actual_subscribe(incoming_tx, pool);
     }
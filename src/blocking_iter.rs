@@ -0,0 +1,105 @@
+use crate::notification::Notification;
+use crate::observable::Observable;
+use crate::scheduler::Scheduler;
+use crate::subscription::SubscriptionState;
+use crate::utils::{self, Subscribed};
+use log::error;
+use std::sync::mpsc::Receiver;
+use std::sync::Arc;
+
+/// Iterates the values of a pipeline on the calling thread, blocking until each one arrives.
+/// Ends after the terminating error or completion, and unsubscribes from the pipeline once dropped.
+/// The scheduler has to run the pipeline on other threads, so the `CurrentThreadScheduler` can't be used.
+pub struct BlockingIter<Item, E> {
+    incoming: Receiver<Notification<Item, E>>,
+    state: Arc<SubscriptionState>,
+}
+
+impl<Item, E> BlockingIter<Item, E>
+where
+    Item: Send + 'static,
+    E: Send + 'static,
+{
+    pub(crate) fn new<S, O>(source: S, scheduler: O) -> Self
+    where
+        S: Observable<Item = Item, Error = E>,
+        O: Scheduler + Clone + Send + 'static,
+    {
+        let Subscribed {
+            incoming, state, ..
+        } = utils::subscribe_channel(source, scheduler);
+        BlockingIter { incoming, state }
+    }
+}
+
+impl<Item, E> Iterator for BlockingIter<Item, E> {
+    type Item = Result<Item, E>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.state.is_closed() {
+            return None;
+        }
+        let result = match self.incoming.recv() {
+            Ok(Notification::Next(message)) => return Some(Ok(message)),
+            Ok(Notification::Error(e)) => Some(Err(e)),
+            Ok(Notification::Complete) => None,
+            Err(_) => {
+                error!("BlockingIter, upstream terminated without completing");
+                None
+            }
+        };
+        self.state.unsubscribe();
+        result
+    }
+}
+
+impl<Item, E> Drop for BlockingIter<Item, E> {
+    fn drop(&mut self) {
+        self.state.unsubscribe();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::create::create;
+    use crate::from_iter::from_iter;
+    use crate::observable::Observable;
+    use crate::observer::Observer;
+    use crate::subscriber::Subscriber;
+    use futures::executor::ThreadPool;
+    use std::sync::mpsc;
+
+    #[test]
+    fn it_iterates_blocking() {
+        let values = from_iter(0..10)
+            .map(|v| v * 2)
+            .into_blocking_iter(ThreadPool::new().unwrap())
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(values.len(), 10);
+        let errors = create(|sender: Subscriber<i32, String>| {
+            sender.next(1).unwrap();
+            sender.error("error".to_string()).unwrap();
+        })
+        .into_blocking_iter(ThreadPool::new().unwrap())
+        .collect::<Vec<_>>();
+        assert_eq!(errors, vec![Ok(1), Err("error".to_string())]);
+    }
+
+    #[test]
+    fn it_unsubscribes_when_dropped() {
+        let (closed_tx, closed_rx) = mpsc::channel();
+        let mut iter = create(move |sender: Subscriber<i32, String>| {
+            let mut i = 0;
+            while sender.next(i).is_ok() {
+                i += 1;
+            }
+            closed_tx.send(()).unwrap();
+        })
+        .into_blocking_iter(ThreadPool::new().unwrap());
+        assert_eq!(iter.next(), Some(Ok(0)));
+        assert_eq!(iter.next(), Some(Ok(1)));
+        drop(iter);
+        closed_rx.recv().unwrap();
+    }
+}
@@ -0,0 +1,109 @@
+use crate::observable::Observable;
+use crate::scheduler::Scheduler;
+use crate::subscriber::Subscriber;
+
+pub struct BoundedObservable<Source> {
+    pub(crate) source: Source,
+    pub(crate) capacity: usize,
+}
+
+impl<Source> Observable for BoundedObservable<Source>
+where
+    Source: Observable,
+{
+    type Item = Source::Item;
+    type Error = Source::Error;
+
+    fn actual_subscribe<O>(self, channel: Subscriber<Self::Item, Self::Error>, pool: O)
+    where
+        O: Scheduler + Clone + Send + 'static,
+    {
+        self.source
+            .actual_subscribe(channel.with_capacity(self.capacity), pool);
+    }
+
+    fn capacity(&self) -> Option<usize> {
+        Some(self.capacity)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::create::create;
+    use crate::observable::Observable;
+    use crate::observer::Observer;
+    use crate::subscriber::Subscriber;
+    use futures::executor::ThreadPool;
+    use std::sync::atomic::{AtomicI32, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    #[test]
+    fn it_blocks_the_producer() {
+        let produced = Arc::new(AtomicI32::new(0));
+        let produced_c = produced.clone();
+        let subscription = create(move |sender: Subscriber<i32, String>| {
+            for i in 0..100 {
+                if sender.next(i).is_err() {
+                    break;
+                }
+                produced_c.fetch_add(1, Ordering::Relaxed);
+            }
+        })
+        .map(|v| {
+            std::thread::sleep(Duration::from_millis(5));
+            v
+        })
+        .bounded(2)
+        .subscribe(|_| {}, ThreadPool::new().unwrap());
+        std::thread::sleep(Duration::from_millis(20));
+        assert!(produced.load(Ordering::Relaxed) < 50);
+        futures::executor::block_on(subscription);
+        assert_eq!(produced.load(Ordering::Relaxed), 100);
+    }
+
+    #[test]
+    fn it_blocks_the_producer_on_a_slow_subscriber() {
+        let produced = Arc::new(AtomicI32::new(0));
+        let produced_c = produced.clone();
+        let subscription = create(move |sender: Subscriber<i32, String>| {
+            for i in 0..1000 {
+                if sender.next(i).is_err() {
+                    break;
+                }
+                produced_c.fetch_add(1, Ordering::Relaxed);
+            }
+        })
+        .map_inline(|v| v)
+        .bounded(2)
+        .subscribe(
+            |_| std::thread::sleep(Duration::from_millis(5)),
+            ThreadPool::new().unwrap(),
+        );
+        std::thread::sleep(Duration::from_millis(100));
+        assert!(produced.load(Ordering::Relaxed) < 100);
+        subscription.unsubscribe();
+    }
+
+    #[test]
+    fn it_emits_all_values() {
+        let collector = Arc::new(AtomicI32::new(0));
+        let collector_c = collector.clone();
+        let subscription = create(|sender: Subscriber<i32, String>| {
+            for i in 0..10 {
+                sender.next(i).unwrap();
+            }
+        })
+        .filter(|v| v % 2 == 0)
+        .map(|v| v * 2)
+        .bounded(0)
+        .subscribe(
+            move |v| {
+                collector_c.fetch_add(v, Ordering::Relaxed);
+            },
+            ThreadPool::new().unwrap(),
+        );
+        futures::executor::block_on(subscription);
+        assert_eq!(collector.load(Ordering::Relaxed), 40);
+    }
+}
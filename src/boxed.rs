@@ -0,0 +1,87 @@
+use crate::deferred_scheduler::DeferredScheduler;
+use crate::observable::Observable;
+use crate::scheduler::Scheduler;
+use crate::subscriber::Subscriber;
+
+type Subscribe<Item, E> = Box<dyn FnOnce(Subscriber<Item, E>, DeferredScheduler) + Send>;
+
+/// An observable whose operators are hidden behind a trait object,
+/// so pipelines of different shapes can be stored, or returned from different match arms, as the same type
+pub struct BoxedObservable<Item, E> {
+    subscribe: Subscribe<Item, E>,
+    capacity: Option<usize>,
+}
+
+impl<Item, E> BoxedObservable<Item, E> {
+    pub fn new<S>(source: S) -> Self
+    where
+        S: Observable<Item = Item, Error = E> + Send + 'static,
+    {
+        Self {
+            capacity: source.capacity(),
+            subscribe: Box::new(move |channel, pool| source.actual_subscribe(channel, pool)),
+        }
+    }
+}
+
+impl<Item, E> Observable for BoxedObservable<Item, E> {
+    type Item = Item;
+    type Error = E;
+
+    /// The boxed operators are always handed a `DeferredScheduler`, which resolves to `pool`,
+    /// as their scheduler type has to be known when boxing them
+    fn actual_subscribe<O>(self, channel: Subscriber<Self::Item, Self::Error>, pool: O)
+    where
+        O: Scheduler + Clone + Send + 'static,
+    {
+        match DeferredScheduler::downcast(&pool) {
+            Some(deferred) => (self.subscribe)(channel, deferred.clone()),
+            None => {
+                let deferred = DeferredScheduler::default();
+                (self.subscribe)(channel, deferred.clone());
+                deferred.resolve_upstream(pool);
+            }
+        }
+    }
+
+    fn capacity(&self) -> Option<usize> {
+        self.capacity
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::boxed::BoxedObservable;
+    use crate::from_iter::from_iter;
+    use crate::observable::Observable;
+    use futures::executor::ThreadPool;
+    use std::convert::Infallible;
+    use std::sync::{Arc, Mutex};
+
+    fn pipeline(config: &str) -> BoxedObservable<i32, Infallible> {
+        let source = from_iter(0..10);
+        match config {
+            "even" => source.filter_inline(|v| v % 2 == 0).boxed(),
+            "doubled" => source.map_inline(|v| v * 2).boxed(),
+            _ => source.boxed(),
+        }
+    }
+
+    #[test]
+    fn it_subscribes_to_boxed_pipelines() {
+        let pool = ThreadPool::new().unwrap();
+        for (config, expected) in [
+            ("even", vec![0, 2, 4, 6, 8]),
+            ("doubled", (0..10).map(|v| v * 2).collect()),
+            ("all", (0..10).collect()),
+        ] {
+            let collector = Arc::new(Mutex::new(vec![]));
+            let collector_c = collector.clone();
+            let subscription = pipeline(config)
+                .map_inline(|v| v)
+                .subscribe(move |v| collector_c.lock().unwrap().push(v), pool.clone());
+            futures::executor::block_on(subscription);
+            assert_eq!(*collector.lock().unwrap(), expected);
+        }
+    }
+}
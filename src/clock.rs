@@ -0,0 +1,17 @@
+use std::time::{Duration, SystemTime};
+
+/// The source of the current time for time based operators, as the duration since the unix epoch
+pub trait Clock {
+    fn now(&self) -> Duration;
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Duration {
+        SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .expect("Could not get epoch seconds")
+    }
+}
@@ -0,0 +1,113 @@
+use crate::notification::Notification;
+use crate::observable::Observable;
+use crate::scheduler::Scheduler;
+use crate::subscription::{Subscription, Terminated, TrySubscription};
+use crate::utils::{self, Subscribed};
+use std::ops::ControlFlow;
+
+/// Subscribes to `source`, adding each value to the accumulator with `add` until it breaks or the stream terminates.
+/// The subscription resolves to the accumulator turned into the result with `finish`, or why the stream ended without completing.
+pub(crate) fn collect<S, A, T, O>(
+    source: S,
+    init: A,
+    mut add: impl FnMut(&mut A, S::Item) -> ControlFlow<()> + Send + 'static,
+    finish: impl FnOnce(A) -> T + Send + 'static,
+    scheduler: O,
+) -> TrySubscription<T, S::Error>
+where
+    S: Observable,
+    S::Item: Send + 'static,
+    S::Error: Send + 'static,
+    A: Send + 'static,
+    T: Send + 'static,
+    O: Scheduler + Clone + Send + 'static,
+{
+    let Subscribed {
+        incoming,
+        state,
+        scheduler,
+    } = utils::subscribe_channel(source, scheduler);
+    let state_c = state.clone();
+    let mut accumulator = Some(init);
+    let mut finish = Some(finish);
+    let handle = utils::receive(&scheduler, incoming, "Subscribe", move |message| {
+        let result = match message {
+            _ if state_c.is_closed() => Err(Terminated::Unsubscribed),
+            Ok(Notification::Next(message)) => match add(accumulator.as_mut().unwrap(), message) {
+                ControlFlow::Continue(()) => return ControlFlow::Continue(()),
+                ControlFlow::Break(()) => Ok(()),
+            },
+            Ok(Notification::Error(e)) => Err(Terminated::Error(e)),
+            Ok(Notification::Complete) => Ok(()),
+            Err(_) => Err(Terminated::Dropped),
+        };
+        state_c.unsubscribe();
+        let finish = finish.take().unwrap();
+        ControlFlow::Break(result.map(|()| finish(accumulator.take().unwrap())))
+    });
+    Subscription::new(handle, state)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::create::create;
+    use crate::from_iter::from_iter;
+    use crate::observable::Observable;
+    use crate::observer::Observer;
+    use crate::subscriber::Subscriber;
+    use crate::subscription::Terminated;
+    use futures::executor::{block_on, ThreadPool};
+    use std::collections::HashMap;
+
+    #[test]
+    fn it_collects() {
+        let pool = ThreadPool::new().unwrap();
+        let values = || from_iter(0..6).map_inline(|v| v * 2);
+        assert_eq!(
+            block_on(values().to_vec(pool.clone())),
+            Ok(vec![0, 2, 4, 6, 8, 10])
+        );
+        assert_eq!(block_on(values().count(pool.clone())), Ok(6));
+        assert_eq!(block_on(values().last(pool.clone())), Ok(Some(10)));
+        assert_eq!(
+            block_on(values().to_hash_map(|v| v % 3, pool.clone())),
+            Ok(HashMap::from([(0, 6), (1, 10), (2, 8)]))
+        );
+        assert_eq!(
+            block_on(values().to_multimap(|v| v % 3, pool.clone())),
+            Ok(HashMap::from([
+                (0, vec![0, 6]),
+                (1, vec![4, 10]),
+                (2, vec![2, 8])
+            ]))
+        );
+        let failing = create(|sender: Subscriber<i32, String>| {
+            sender.next(1).unwrap();
+            sender.error("error".to_string()).unwrap();
+        });
+        assert_eq!(
+            block_on(failing.to_vec(pool)),
+            Err(Terminated::Error("error".to_string()))
+        );
+    }
+
+    #[test]
+    fn it_resolves_first_without_waiting_for_completion() {
+        let pool = ThreadPool::new().unwrap();
+        assert_eq!(block_on(from_iter(5..).first(pool.clone())), Ok(Some(5)));
+        assert_eq!(block_on(from_iter(0..0).first(pool)), Ok(None::<i32>));
+    }
+
+    #[test]
+    fn it_resolves_to_unsubscribed() {
+        let values = create(|sender: Subscriber<i32, String>| {
+            let mut i = 0;
+            while sender.next(i).is_ok() {
+                i += 1;
+            }
+        })
+        .to_vec(ThreadPool::new().unwrap());
+        values.unsubscribe();
+        assert_eq!(block_on(values), Err(Terminated::Unsubscribed));
+    }
+}
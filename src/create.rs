@@ -1,17 +1,19 @@
+use crate::notification::Notification;
 use crate::observable::Observable;
 use crate::scheduler::Scheduler;
+use crate::subscriber::Subscriber;
+use crate::utils;
 use log::trace;
 use std::marker::PhantomData;
-use std::sync::mpsc::Sender;
 
-pub struct Create<I, Item> {
+pub struct Create<I, Item, E> {
     create_function: I,
-    _marker: PhantomData<Item>,
+    _marker: PhantomData<(Item, E)>,
 }
 
-pub fn create<I, Item>(create_function: I) -> Create<I, Item>
+pub fn create<I, Item, E>(create_function: I) -> Create<I, Item, E>
 where
-    I: FnMut(Sender<std::io::Result<Item>>),
+    I: FnMut(Subscriber<Item, E>),
 {
     Create {
         create_function,
@@ -19,19 +21,23 @@ where
     }
 }
 
-impl<I, Item> Observable for Create<I, Item>
+impl<I, Item, E> Observable for Create<I, Item, E>
 where
-    I: FnMut(Sender<std::io::Result<Item>>) + Send + 'static,
+    I: FnMut(Subscriber<Item, E>) + Send + 'static,
     Item: Send + 'static,
+    E: Send + 'static,
 {
     type Item = Item;
+    type Error = E;
 
-    fn actual_subscribe<O>(mut self, channel: Sender<std::io::Result<Self::Item>>, pool: O)
+    fn actual_subscribe<O>(mut self, channel: Subscriber<Self::Item, Self::Error>, pool: O)
     where
         O: Scheduler,
     {
-        pool.schedule(move || {
-            (self.create_function)(channel);
+        utils::schedule_blocking(&pool, move || {
+            (self.create_function)(channel.clone());
+            // Only reached if the create function did not panic, and dropped if it terminated itself
+            let _ = channel.send(Notification::Complete);
             trace!("Create finished");
         })
         .forget();
@@ -44,6 +50,7 @@ mod test {
     use crate::observable::Observable;
     use crate::observer::Observer;
     use futures::executor::ThreadPool;
+    use std::convert::Infallible;
     use std::sync::atomic::{AtomicI32, Ordering};
     use std::sync::Arc;
 
@@ -52,7 +59,7 @@ mod test {
         let collector = Arc::new(AtomicI32::new(0));
         let collector_c = collector.clone();
         let pool = ThreadPool::new().unwrap();
-        let handle = create(|sender| {
+        let handle = create::<_, _, Infallible>(|sender| {
             sender.next(1).unwrap();
             sender.next(2).unwrap();
             sender.next(3).unwrap();
@@ -72,7 +79,7 @@ mod test {
         let collector = Arc::new(AtomicI32::new(0));
         let collector_c = collector.clone();
         let mut _test = 0;
-        let handle = create(move |sender| {
+        let handle = create::<_, _, Infallible>(move |sender| {
             sender.next(1).unwrap();
             sender.next(2).unwrap();
             sender.next(3).unwrap();
@@ -0,0 +1,263 @@
+use crate::scheduler::{LoopStep, Scheduler, Wait};
+use futures::future::{AbortHandle, BoxFuture, RemoteHandle};
+use futures::task::noop_waker_ref;
+use futures::FutureExt;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Runs the scheduled tasks one after another on the thread calling `run`, in the order they were scheduled.
+/// Operator loops are polled instead of blocking, so the tasks of a pipeline run in the same order every time.
+/// Timers fire in between once due on the wall clock, so where they land relative to the tasks depends on timing,
+/// use the `TestScheduler` to control time.
+/// Nothing may block on another task though, which rules out bounded channels and producers waiting for consumers.
+#[derive(Clone, Default)]
+pub struct CurrentThreadScheduler {
+    tasks: Arc<Mutex<VecDeque<BoxFuture<'static, ()>>>>,
+    timers: Arc<Mutex<Vec<Timer>>>,
+    progressed: Arc<AtomicBool>,
+}
+
+struct Timer {
+    due: Instant,
+    /// Repeating timers are due again after their interval
+    interval: Option<Duration>,
+    task: Box<dyn FnMut() + Send>,
+    abort_handle: AbortHandle,
+}
+
+impl CurrentThreadScheduler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Runs the scheduled tasks until all of them finished, or all remaining ones wait for messages,
+    /// and no timers are left. While only timers are left, it sleeps until the next one is due,
+    /// so repeating timers have to be aborted for it to return.
+    pub fn run(&self) {
+        loop {
+            self.run_tasks();
+            let Some(due) = self.next_due() else {
+                break;
+            };
+            thread::sleep(due.saturating_duration_since(Instant::now()));
+        }
+    }
+
+    fn run_tasks(&self) {
+        let mut context = Context::from_waker(noop_waker_ref());
+        // The number of tasks in a row which had nothing to do
+        let mut idle = 0;
+        loop {
+            if self.fire_timers() {
+                idle = 0;
+            }
+            let Some(mut task) = self.tasks.lock().unwrap().pop_front() else {
+                break;
+            };
+            self.progressed.store(false, Ordering::Relaxed);
+            match task.as_mut().poll(&mut context) {
+                Poll::Ready(()) => idle = 0,
+                Poll::Pending => {
+                    if self.progressed.load(Ordering::Relaxed) {
+                        idle = 0;
+                    } else {
+                        idle += 1;
+                    }
+                    self.tasks.lock().unwrap().push_back(task);
+                }
+            }
+            if idle > 0 && idle >= self.tasks.lock().unwrap().len() {
+                break;
+            }
+        }
+    }
+
+    /// Fires every timer due by now, returning whether there was any
+    fn fire_timers(&self) -> bool {
+        let now = Instant::now();
+        let due = {
+            let mut timers = self.timers.lock().unwrap();
+            let (due, pending) = std::mem::take(&mut *timers)
+                .into_iter()
+                .partition::<Vec<_>, _>(|timer| timer.due <= now);
+            *timers = pending;
+            due
+        };
+        let fired = !due.is_empty();
+        for mut timer in due {
+            if timer.abort_handle.is_aborted() {
+                continue;
+            }
+            (timer.task)();
+            if let Some(interval) = timer.interval {
+                timer.due += interval;
+                self.timers.lock().unwrap().push(timer);
+            }
+        }
+        fired
+    }
+
+    /// When the earliest timer which is not aborted is due
+    fn next_due(&self) -> Option<Instant> {
+        let mut timers = self.timers.lock().unwrap();
+        timers.retain(|timer| !timer.abort_handle.is_aborted());
+        timers.iter().map(|timer| timer.due).min()
+    }
+
+    fn push(&self, task: BoxFuture<'static, ()>) {
+        self.tasks.lock().unwrap().push_back(task);
+    }
+
+    fn schedule_timer(
+        &self,
+        due: Instant,
+        interval: Option<Duration>,
+        task: impl FnMut() + Send + 'static,
+    ) -> AbortHandle {
+        let (abort_handle, _) = AbortHandle::new_pair();
+        self.timers.lock().unwrap().push(Timer {
+            due,
+            interval,
+            task: Box::new(task),
+            abort_handle: abort_handle.clone(),
+        });
+        abort_handle
+    }
+}
+
+impl Scheduler for CurrentThreadScheduler {
+    fn schedule<R>(&self, task: impl FnOnce() -> R + Send + 'static) -> RemoteHandle<R>
+    where
+        R: Send + 'static,
+    {
+        let (remote, remote_handle) = async { (task)() }.remote_handle();
+        self.push(remote.boxed());
+        remote_handle
+    }
+
+    fn schedule_loop<R>(
+        &self,
+        mut step: impl FnMut(Wait) -> LoopStep<R> + Send + 'static,
+    ) -> RemoteHandle<R>
+    where
+        R: Send + 'static,
+    {
+        let progressed = self.progressed.clone();
+        let (remote, remote_handle) = futures::future::poll_fn(move |_| match step(Wait::Poll) {
+            LoopStep::Received => {
+                progressed.store(true, Ordering::Relaxed);
+                Poll::Pending
+            }
+            LoopStep::Empty => Poll::Pending,
+            LoopStep::Finished(result) => Poll::Ready(result),
+        })
+        .remote_handle();
+        self.push(remote.boxed());
+        remote_handle
+    }
+
+    fn schedule_repeating<F>(&self, task: F, interval: Duration) -> AbortHandle
+    where
+        F: Fn() + Send + 'static,
+    {
+        self.schedule_timer(Instant::now() + interval, Some(interval), task)
+    }
+
+    fn schedule_after<F>(&self, delay: Duration, task: F) -> AbortHandle
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        let mut task = Some(task);
+        self.schedule_timer(Instant::now() + delay, None, move || {
+            if let Some(task) = task.take() {
+                (task)();
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::current_thread_scheduler::CurrentThreadScheduler;
+    use crate::from_iter::from_iter;
+    use crate::observable::Observable;
+    use crate::scheduler::Scheduler;
+    use futures::future::AbortHandle;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex, OnceLock};
+    use std::time::Duration;
+
+    #[test]
+    fn it_runs_tasks_in_order() {
+        let scheduler = CurrentThreadScheduler::new();
+        let collector = Arc::new(Mutex::new(vec![]));
+        for i in 0..5 {
+            let collector_c = collector.clone();
+            scheduler
+                .schedule(move || collector_c.lock().unwrap().push(i))
+                .forget();
+        }
+        assert!(collector.lock().unwrap().is_empty());
+        scheduler.run();
+        assert_eq!(*collector.lock().unwrap(), vec![0, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn it_runs_pipelines() {
+        let scheduler = CurrentThreadScheduler::new();
+        let collector = Arc::new(Mutex::new(vec![]));
+        let collector_c = collector.clone();
+        let subscription = from_iter(0..10)
+            .map(|v| v * 2)
+            .filter(|v| v % 3 != 0)
+            .reduce(vec![], |mut acc, v| {
+                acc.push(v);
+                acc
+            })
+            .subscribe(
+                move |v| collector_c.lock().unwrap().extend(v),
+                scheduler.clone(),
+            );
+        scheduler.run();
+        futures::executor::block_on(subscription);
+        assert_eq!(*collector.lock().unwrap(), vec![2, 4, 8, 10, 14, 16]);
+    }
+
+    #[test]
+    fn it_runs_until_repeating_timers_are_aborted() {
+        let scheduler = CurrentThreadScheduler::new();
+        let fired = Arc::new(AtomicUsize::new(0));
+        let fired_c = fired.clone();
+        let handle = Arc::new(OnceLock::<AbortHandle>::new());
+        let handle_c = handle.clone();
+        let abort_handle = scheduler.schedule_repeating(
+            move || {
+                if fired_c.fetch_add(1, Ordering::Relaxed) == 2 {
+                    handle_c.get().unwrap().abort();
+                }
+            },
+            Duration::from_millis(5),
+        );
+        handle.set(abort_handle).unwrap();
+        scheduler.run();
+        assert_eq!(fired.load(Ordering::Relaxed), 3);
+    }
+
+    #[test]
+    fn it_runs_delayed_tasks_before_returning() {
+        let scheduler = CurrentThreadScheduler::new();
+        let fired = Arc::new(AtomicUsize::new(0));
+        let fired_c = fired.clone();
+        scheduler.schedule_after(Duration::from_millis(10), move || {
+            fired_c.fetch_add(1, Ordering::Relaxed);
+        });
+        let aborted = scheduler.schedule_after(Duration::from_millis(10), || unreachable!());
+        aborted.abort();
+        scheduler.run();
+        assert_eq!(fired.load(Ordering::Relaxed), 1);
+    }
+}
@@ -0,0 +1,281 @@
+use crate::clock::{Clock, SystemClock};
+use crate::scheduler::{self, BoxedStep, LoopStep, Scheduler, Wait};
+use futures::future::{AbortHandle, BoxFuture, RemoteHandle};
+use futures::FutureExt;
+use std::any::Any;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+
+type Runner = Box<dyn Fn(Job) + Send + Sync>;
+
+enum Job {
+    Task(BoxFuture<'static, ()>),
+    Detached(Box<dyn FnOnce() + Send>),
+    Loop(BoxedStep),
+    Repeating(Box<dyn Fn() + Send>, Duration, Arc<OnceLock<AbortHandle>>),
+    After(Box<dyn FnOnce() + Send>, Duration),
+    At(Box<dyn FnOnce() + Send>, Duration),
+}
+
+/// Collects everything scheduled on it until it is resolved to the scheduler it should have run on.
+/// `subscribe` hands one to the pipeline, so that `observe_on` can move the operators below it
+/// to another scheduler after they were subscribed. The operators above are handed one created by `upstream`.
+#[derive(Clone, Default)]
+pub(crate) struct DeferredScheduler {
+    inner: Arc<Deferred>,
+}
+
+#[derive(Default)]
+struct Deferred {
+    pending: Mutex<Vec<Job>>,
+    target: OnceLock<Runner>,
+    clock: OnceLock<Arc<dyn Clock + Send + Sync>>,
+    /// Shared with every scheduler created upstream of the one handed to the pipeline
+    remaining: Arc<Mutex<Remaining>>,
+    /// The scheduler below a `subscribe_on`, if this one was handed to the operators above it
+    downstream: Option<DeferredScheduler>,
+}
+
+/// The schedulers created by `upstream` until `resolve_upstream` is called, then how it resolved them
+enum Remaining {
+    Unresolved(Vec<DeferredScheduler>),
+    Resolved(Arc<dyn Fn(&DeferredScheduler) + Send + Sync>),
+}
+
+impl Default for Remaining {
+    fn default() -> Self {
+        Remaining::Unresolved(vec![])
+    }
+}
+
+impl DeferredScheduler {
+    /// The scheduler operators are handed, unless `actual_subscribe` was called directly with another one.
+    /// This relies on every operator passing the scheduler it was handed on to its sources, see `Observable::actual_subscribe`.
+    pub(crate) fn downcast<O>(pool: &O) -> Option<&DeferredScheduler>
+    where
+        O: 'static,
+    {
+        (pool as &dyn Any).downcast_ref()
+    }
+
+    /// Creates the scheduler a `subscribe_on` hands to the operators above it, which it resolves itself.
+    /// An `observe_on` above moving those operators moves the ones below the `subscribe_on` as well.
+    pub(crate) fn above<O>(downstream: &O) -> DeferredScheduler
+    where
+        O: 'static,
+    {
+        DeferredScheduler {
+            inner: Arc::new(Deferred {
+                downstream: Self::downcast(downstream).cloned(),
+                ..Deferred::default()
+            }),
+        }
+    }
+
+    /// Creates the scheduler for the operators above the one this scheduler was handed to.
+    /// If `resolve_upstream` was called already, e.g. for the inner observables of `flatten`, it is resolved right away.
+    pub(crate) fn upstream(&self) -> DeferredScheduler {
+        let upstream = DeferredScheduler {
+            inner: Arc::new(Deferred {
+                remaining: self.inner.remaining.clone(),
+                ..Deferred::default()
+            }),
+        };
+        let mut remaining = self.inner.remaining.lock().unwrap();
+        let resolve = match &mut *remaining {
+            Remaining::Unresolved(schedulers) => {
+                schedulers.push(upstream.clone());
+                return upstream;
+            }
+            Remaining::Resolved(resolve) => resolve.clone(),
+        };
+        drop(remaining);
+        resolve(&upstream);
+        upstream
+    }
+
+    /// Moves the operators this scheduler was handed to, and those below up to the next `observe_on`, to `pool`
+    pub(crate) fn observe_on<S>(&self, pool: S)
+    where
+        S: Scheduler + Clone + Send + 'static,
+    {
+        if self.resolve(pool.clone()) {
+            if let Some(downstream) = &self.inner.downstream {
+                downstream.observe_on(pool);
+            }
+        }
+    }
+
+    /// Runs everything scheduled so far, and from now on, on `pool`, returning false if it was resolved before
+    fn resolve<S>(&self, pool: S) -> bool
+    where
+        S: Scheduler + Clone + Send + 'static,
+    {
+        let _ = self.inner.clock.set(pool.clock());
+        let pool = Mutex::new(pool);
+        let runner: Runner = Box::new(move |job| {
+            let pool = pool.lock().unwrap();
+            match job {
+                Job::Task(remote) => pool
+                    .schedule(move || {
+                        let _ = remote.now_or_never();
+                    })
+                    .forget(),
+                Job::Detached(task) => pool.schedule_detached(task),
+                Job::Loop(step) => pool.schedule_loop(step).forget(),
+                Job::Repeating(task, interval, handle) => {
+                    let _ = handle.set(pool.schedule_repeating(task, interval));
+                }
+                Job::After(task, delay) => {
+                    pool.schedule_after(delay, task);
+                }
+                Job::At(task, time) => {
+                    pool.schedule_at(time, task);
+                }
+            }
+        });
+        let mut pending = self.inner.pending.lock().unwrap();
+        if self.inner.target.set(runner).is_err() {
+            return false;
+        }
+        let jobs = std::mem::take(&mut *pending);
+        drop(pending);
+        let runner = self.inner.target.get().unwrap();
+        jobs.into_iter().for_each(runner);
+        true
+    }
+
+    /// Resolves this scheduler, and every one created upstream of it which no `observe_on` resolved, to `pool`.
+    /// Schedulers created upstream later on are resolved to `pool` as well.
+    pub(crate) fn resolve_upstream<S>(&self, pool: S)
+    where
+        S: Scheduler + Clone + Send + 'static,
+    {
+        self.resolve(pool.clone());
+        let pool = Mutex::new(pool);
+        let resolve: Arc<dyn Fn(&DeferredScheduler) + Send + Sync> = Arc::new(move |scheduler| {
+            scheduler.resolve(pool.lock().unwrap().clone());
+        });
+        let remaining = std::mem::replace(
+            &mut *self.inner.remaining.lock().unwrap(),
+            Remaining::Resolved(resolve.clone()),
+        );
+        if let Remaining::Unresolved(schedulers) = remaining {
+            schedulers.iter().for_each(|scheduler| resolve(scheduler));
+        }
+    }
+
+    fn submit(&self, job: Job) {
+        // Once resolved, jobs skip the lock guarding the pending ones
+        if let Some(runner) = self.inner.target.get() {
+            return runner(job);
+        }
+        let mut pending = self.inner.pending.lock().unwrap();
+        match self.inner.target.get() {
+            Some(runner) => {
+                drop(pending);
+                runner(job);
+            }
+            None => pending.push(job),
+        }
+    }
+}
+
+impl Scheduler for DeferredScheduler {
+    fn schedule<R>(&self, task: impl FnOnce() -> R + Send + 'static) -> RemoteHandle<R>
+    where
+        R: Send + 'static,
+    {
+        let (remote, remote_handle) = async { (task)() }.remote_handle();
+        self.submit(Job::Task(remote.boxed()));
+        remote_handle
+    }
+
+    /// Skips the handle of `schedule`, which would wrap the one of the scheduler it resolves to
+    fn schedule_detached(&self, task: impl FnOnce() + Send + 'static) {
+        self.submit(Job::Detached(Box::new(task)));
+    }
+
+    fn schedule_loop<R>(
+        &self,
+        step: impl FnMut(Wait) -> LoopStep<R> + Send + 'static,
+    ) -> RemoteHandle<R>
+    where
+        R: Send + 'static,
+    {
+        let (step, remote_handle) = scheduler::box_loop(step);
+        self.submit(Job::Loop(step));
+        remote_handle
+    }
+
+    fn schedule_repeating<F>(&self, task: F, interval: Duration) -> AbortHandle
+    where
+        F: Fn() + Send + 'static,
+    {
+        let (abort_handle, _) = AbortHandle::new_pair();
+        let abort_handle_c = abort_handle.clone();
+        let inner_handle = Arc::new(OnceLock::<AbortHandle>::new());
+        let inner_handle_c = inner_handle.clone();
+        self.submit(Job::Repeating(
+            Box::new(move || {
+                if abort_handle_c.is_aborted() {
+                    if let Some(inner_handle) = inner_handle_c.get() {
+                        inner_handle.abort();
+                    }
+                } else {
+                    (task)();
+                }
+            }),
+            interval,
+            inner_handle,
+        ));
+        abort_handle
+    }
+
+    /// The task checks for the abort itself, as the scheduler's handle only exists once resolved
+    fn schedule_after<F>(&self, delay: Duration, task: F) -> AbortHandle
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        let (abort_handle, task) = abortable_once(task);
+        self.submit(Job::After(task, delay));
+        abort_handle
+    }
+
+    fn schedule_at<F>(&self, time: Duration, task: F) -> AbortHandle
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        let (abort_handle, task) = abortable_once(task);
+        self.submit(Job::At(task, time));
+        abort_handle
+    }
+
+    /// Reads the clock of the scheduler this one resolves to, once it is resolved
+    fn clock(&self) -> Arc<dyn Clock + Send + Sync> {
+        Arc::new(self.clone())
+    }
+}
+
+fn abortable_once<F>(task: F) -> (AbortHandle, Box<dyn FnOnce() + Send>)
+where
+    F: FnOnce() + Send + 'static,
+{
+    let (abort_handle, _) = AbortHandle::new_pair();
+    let abort_handle_c = abort_handle.clone();
+    let task = Box::new(move || {
+        if !abort_handle_c.is_aborted() {
+            (task)();
+        }
+    });
+    (abort_handle, task)
+}
+
+impl Clock for DeferredScheduler {
+    fn now(&self) -> Duration {
+        match self.inner.clock.get() {
+            Some(clock) => clock.now(),
+            None => SystemClock.now(),
+        }
+    }
+}
@@ -0,0 +1,180 @@
+use crate::clock::Clock;
+use crate::scheduler::{self, BoxedStep, LoopStep, Scheduler, Wait};
+use futures::future::{AbortHandle, RemoteHandle};
+use futures::FutureExt;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// The object safe counterpart of `Scheduler`, implemented for every scheduler,
+/// so schedulers can be chosen at runtime and stored as `Arc<dyn DynScheduler>`.
+/// Wrap it in a `SchedulerHandle` to subscribe with it.
+pub trait DynScheduler: Send + Sync {
+    fn schedule_boxed(&self, task: Box<dyn FnOnce() + Send>);
+    fn schedule_loop_boxed(&self, step: BoxedStep);
+    fn schedule_repeating_boxed(
+        &self,
+        task: Box<dyn Fn() + Send>,
+        interval: Duration,
+    ) -> AbortHandle;
+    fn schedule_after_boxed(&self, delay: Duration, task: Box<dyn FnOnce() + Send>) -> AbortHandle;
+    fn schedule_at_boxed(&self, time: Duration, task: Box<dyn FnOnce() + Send>) -> AbortHandle;
+    fn dyn_clock(&self) -> Arc<dyn Clock + Send + Sync>;
+}
+
+impl<S> DynScheduler for S
+where
+    S: Scheduler + Send + Sync,
+{
+    fn schedule_boxed(&self, task: Box<dyn FnOnce() + Send>) {
+        self.schedule_detached(task);
+    }
+
+    fn schedule_loop_boxed(&self, step: BoxedStep) {
+        self.schedule_loop(step).forget();
+    }
+
+    fn schedule_repeating_boxed(
+        &self,
+        task: Box<dyn Fn() + Send>,
+        interval: Duration,
+    ) -> AbortHandle {
+        self.schedule_repeating(task, interval)
+    }
+
+    fn schedule_after_boxed(&self, delay: Duration, task: Box<dyn FnOnce() + Send>) -> AbortHandle {
+        self.schedule_after(delay, task)
+    }
+
+    fn schedule_at_boxed(&self, time: Duration, task: Box<dyn FnOnce() + Send>) -> AbortHandle {
+        self.schedule_at(time, task)
+    }
+
+    fn dyn_clock(&self) -> Arc<dyn Clock + Send + Sync> {
+        self.clock()
+    }
+}
+
+/// A cloneable `Scheduler` delegating to any scheduler behind a `dyn DynScheduler`,
+/// so operators are only instantiated once for all of them
+#[derive(Clone)]
+pub struct SchedulerHandle {
+    scheduler: Arc<dyn DynScheduler>,
+}
+
+impl SchedulerHandle {
+    pub fn new<S>(scheduler: S) -> Self
+    where
+        S: Scheduler + Send + Sync + 'static,
+    {
+        Self {
+            scheduler: Arc::new(scheduler),
+        }
+    }
+}
+
+impl From<Arc<dyn DynScheduler>> for SchedulerHandle {
+    fn from(scheduler: Arc<dyn DynScheduler>) -> Self {
+        Self { scheduler }
+    }
+}
+
+impl Scheduler for SchedulerHandle {
+    fn schedule<R>(&self, task: impl FnOnce() -> R + Send + 'static) -> RemoteHandle<R>
+    where
+        R: Send + 'static,
+    {
+        let (remote, remote_handle) = async { (task)() }.remote_handle();
+        self.scheduler.schedule_boxed(Box::new(move || {
+            let _ = remote.now_or_never();
+        }));
+        remote_handle
+    }
+
+    fn schedule_detached(&self, task: impl FnOnce() + Send + 'static) {
+        self.scheduler.schedule_boxed(Box::new(task));
+    }
+
+    fn schedule_loop<R>(
+        &self,
+        step: impl FnMut(Wait) -> LoopStep<R> + Send + 'static,
+    ) -> RemoteHandle<R>
+    where
+        R: Send + 'static,
+    {
+        let (step, remote_handle) = scheduler::box_loop(step);
+        self.scheduler.schedule_loop_boxed(step);
+        remote_handle
+    }
+
+    fn schedule_repeating<F>(&self, task: F, interval: Duration) -> AbortHandle
+    where
+        F: Fn() + Send + 'static,
+    {
+        self.scheduler
+            .schedule_repeating_boxed(Box::new(task), interval)
+    }
+
+    fn schedule_after<F>(&self, delay: Duration, task: F) -> AbortHandle
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        self.scheduler.schedule_after_boxed(delay, Box::new(task))
+    }
+
+    fn schedule_at<F>(&self, time: Duration, task: F) -> AbortHandle
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        self.scheduler.schedule_at_boxed(time, Box::new(task))
+    }
+
+    fn clock(&self) -> Arc<dyn Clock + Send + Sync> {
+        self.scheduler.dyn_clock()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::current_thread_scheduler::CurrentThreadScheduler;
+    use crate::dyn_scheduler::{DynScheduler, SchedulerHandle};
+    use crate::from_iter::from_iter;
+    use crate::observable::Observable;
+    use crate::scheduler::Scheduler;
+    use crate::test_scheduler::TestScheduler;
+    use futures::executor::ThreadPool;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    #[test]
+    fn it_chooses_schedulers_at_runtime() {
+        let current_thread = CurrentThreadScheduler::new();
+        for name in ["thread-pool", "current-thread"] {
+            let scheduler: Arc<dyn DynScheduler> = match name {
+                "thread-pool" => Arc::new(ThreadPool::new().unwrap()),
+                _ => Arc::new(current_thread.clone()),
+            };
+            let collector = Arc::new(Mutex::new(vec![]));
+            let collector_c = collector.clone();
+            let subscription = from_iter(0..5)
+                .map(|v| v * 2)
+                .reduce(0, |acc, v| acc + v)
+                .subscribe(
+                    move |v| collector_c.lock().unwrap().push(v),
+                    SchedulerHandle::from(scheduler),
+                );
+            current_thread.run();
+            futures::executor::block_on(subscription);
+            assert_eq!(*collector.lock().unwrap(), vec![20]);
+        }
+    }
+
+    #[test]
+    fn it_reads_the_clock_of_the_scheduler() {
+        let scheduler = TestScheduler::new();
+        scheduler.advance_by(Duration::from_millis(5));
+        // Both traits are in scope, which must not make `clock` ambiguous
+        assert_eq!(scheduler.clock().now(), Duration::from_millis(5));
+        let handle = SchedulerHandle::new(scheduler);
+        assert_eq!(handle.clock().now(), Duration::from_millis(5));
+    }
+}
@@ -1,12 +1,13 @@
+use crate::notification::Notification;
 use crate::observable::Observable;
-use std::io;
-use std::io::ErrorKind;
 
 use crate::scheduler::Scheduler;
+use crate::subscriber::Subscriber;
+use crate::utils;
+use crate::utils::{Completion, Permits};
 
-use log::{error, trace};
-use std::sync::mpsc;
-use std::sync::mpsc::Sender;
+use std::ops::ControlFlow;
+use std::sync::Arc;
 
 pub struct FilterOp<S, F> {
     pub(crate) source: S,
@@ -17,44 +18,102 @@ impl<S, F> Observable for FilterOp<S, F>
 where
     S: Observable,
     S::Item: Send + 'static,
+    S::Error: Send + 'static,
     F: Fn(&S::Item) -> bool + Clone + Send + 'static,
 {
     type Item = S::Item;
+    type Error = S::Error;
 
-    fn actual_subscribe<O>(self, channel: Sender<io::Result<Self::Item>>, pool: O)
+    fn actual_subscribe<O>(self, channel: Subscriber<Self::Item, Self::Error>, pool: O)
     where
         O: Scheduler + Clone + Send + 'static,
     {
-        let (incoming_tx, incoming_rx) = mpsc::channel::<io::Result<S::Item>>();
+        let (incoming_tx, incoming_rx) = channel.upstream::<S::Item, S::Error>();
         let pool_c = pool.clone();
-        pool.schedule(move || {
-            loop {
-                let message = incoming_rx.recv();
-                let channel_c = channel.clone();
-                let func_c = self.func.clone();
-                let pool_cc = pool_c.clone();
-                match message {
-                    Ok(Ok(message)) => {
-                        pool_cc
-                            .schedule(move || {
-                                if (func_c)(&message) {
-                                    channel_c.send(Ok(message)).unwrap()
-                                }
-                            })
-                            .forget();
-                    }
-                    Ok(Err(e)) => {
-                        error!("Map, inner unwrap: {:?}", e.to_string());
-                        channel
-                            .send(Err(io::Error::new(ErrorKind::Other, e)))
-                            .unwrap();
-                        break;
+        let completion = Arc::new(Completion::new(channel.clone()));
+        let permits = channel
+            .capacity()
+            .map(|capacity| Arc::new(Permits::new(capacity)));
+        utils::receive_with_permits(
+            &pool,
+            incoming_rx,
+            permits,
+            "Filter",
+            move |message, permit| match message {
+                Ok(Notification::Next(_)) if channel.is_closed() => ControlFlow::Break(()),
+                Ok(Notification::Next(message)) => {
+                    let channel_c = channel.clone();
+                    let func_c = self.func.clone();
+                    let completion_c = completion.clone();
+                    pool_c.schedule_detached(move || {
+                        if (func_c)(&message) {
+                            let _ = channel_c.send(Notification::Next(message));
+                        }
+                        drop(permit);
+                        drop(completion_c);
+                    });
+                    ControlFlow::Continue(())
+                }
+                Ok(Notification::Error(e)) => {
+                    let _ = channel.send(Notification::Error(e));
+                    ControlFlow::Break(())
+                }
+                Ok(Notification::Complete) => {
+                    completion.complete();
+                    ControlFlow::Break(())
+                }
+                Err(_) => ControlFlow::Break(()), // Upstream terminated without completing
+            },
+        )
+        .forget();
+        self.source.actual_subscribe(incoming_tx, pool);
+    }
+}
+
+/// Applies the predicate on the operator's own loop, without scheduling a task per value
+pub struct FilterInlineOp<S, F> {
+    pub(crate) source: S,
+    pub(crate) func: F,
+}
+
+impl<S, F> Observable for FilterInlineOp<S, F>
+where
+    S: Observable,
+    S::Item: Send + 'static,
+    S::Error: Send + 'static,
+    F: Fn(&S::Item) -> bool + Send + 'static,
+{
+    type Item = S::Item;
+    type Error = S::Error;
+
+    fn actual_subscribe<O>(self, channel: Subscriber<Self::Item, Self::Error>, pool: O)
+    where
+        O: Scheduler + Clone + Send + 'static,
+    {
+        let (incoming_tx, incoming_rx) = channel.upstream::<S::Item, S::Error>();
+        let func = self.func;
+        utils::receive(
+            &pool,
+            incoming_rx,
+            "FilterInline",
+            move |message| match message {
+                Ok(Notification::Next(message)) => {
+                    if (func)(&message) && channel.send(Notification::Next(message)).is_err() {
+                        return ControlFlow::Break(());
                     }
-                    Err(_) => break, // Channel closed
+                    ControlFlow::Continue(())
+                }
+                Ok(Notification::Error(e)) => {
+                    let _ = channel.send(Notification::Error(e));
+                    ControlFlow::Break(())
                 }
-            }
-            trace!("Filter finished");
-        })
+                Ok(Notification::Complete) => {
+                    let _ = channel.send(Notification::Complete);
+                    ControlFlow::Break(())
+                }
+                Err(_) => ControlFlow::Break(()), // Upstream terminated without completing
+            },
+        )
         .forget();
         self.source.actual_subscribe(incoming_tx, pool);
     }
@@ -82,4 +141,18 @@ mod tests {
         futures::executor::block_on(handle);
         assert_eq!(collector_c.load(Ordering::Relaxed), 20);
     }
+
+    #[test]
+    fn it_filters_inline() {
+        let collector = Arc::new(AtomicI32::new(0));
+        let collector_c = collector.clone();
+        let handle = from_iter(0..10).filter_inline(|v| v % 2 == 0).subscribe(
+            move |v| {
+                collector.fetch_add(v, Ordering::Relaxed);
+            },
+            ThreadPool::new().unwrap(),
+        );
+        futures::executor::block_on(handle);
+        assert_eq!(collector_c.load(Ordering::Relaxed), 20);
+    }
 }
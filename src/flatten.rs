@@ -1,11 +1,11 @@
-use log::{error, trace};
-use std::io;
-use std::io::ErrorKind;
-use std::sync::mpsc;
-use std::sync::mpsc::Sender;
+use std::ops::ControlFlow;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
 
+use crate::notification::Notification;
 use crate::observable::Observable;
 use crate::scheduler::Scheduler;
+use crate::subscriber::Subscriber;
 use crate::utils;
 
 pub struct FlattenObservable<S> {
@@ -15,41 +15,74 @@ pub struct FlattenObservable<S> {
 impl<S> Observable for FlattenObservable<S>
 where
     S: Observable,
-    S::Item: Observable + Send + 'static,
+    S::Item: Observable<Error = S::Error> + Send + 'static,
     <S::Item as Observable>::Item: Send + 'static,
+    S::Error: Send + 'static,
 {
     type Item = <S::Item as Observable>::Item;
+    type Error = S::Error;
 
-    fn actual_subscribe<O>(self, channel: Sender<io::Result<Self::Item>>, pool: O)
+    fn actual_subscribe<O>(self, channel: Subscriber<Self::Item, Self::Error>, pool: O)
     where
         O: Scheduler + Clone + Send + 'static,
     {
-        let (incoming_tx, incoming_rx) = mpsc::channel::<io::Result<S::Item>>();
+        let (incoming_tx, incoming_rx) = channel.upstream::<S::Item, S::Error>();
         let (subscriber_tx, subscriber_rx) =
-            mpsc::channel::<io::Result<<S::Item as Observable>::Item>>();
+            channel.upstream::<<S::Item as Observable>::Item, S::Error>();
+        // The outer observable and every inner observable still running
+        let pending = Arc::new(AtomicUsize::new(1));
+        let pending_c = pending.clone();
         let pool_c = pool.clone();
         let channel_c = channel.clone();
-        pool.schedule(move || {
-            loop {
-                let message = incoming_rx.recv();
-                match message {
-                    Ok(Ok(message)) => {
-                        message.actual_subscribe(subscriber_tx.clone(), pool_c.clone());
+        utils::receive(
+            &pool,
+            incoming_rx,
+            "Flatten",
+            move |message| match message {
+                Ok(Notification::Next(message)) => {
+                    pending_c.fetch_add(1, Ordering::AcqRel);
+                    // A subscriber of its own, so an inner observable terminating twice is only counted once
+                    message.actual_subscribe(subscriber_tx.fork(), pool_c.clone());
+                    ControlFlow::Continue(())
+                }
+                Ok(Notification::Error(e)) => {
+                    let _ = channel_c.send(Notification::Error(e));
+                    ControlFlow::Break(())
+                }
+                Ok(Notification::Complete) => {
+                    if pending_c.fetch_sub(1, Ordering::AcqRel) == 1 {
+                        let _ = channel_c.send(Notification::Complete);
                     }
-                    Ok(Err(e)) => {
-                        error!("Flatten: {:?}", e.to_string());
-                        channel_c
-                            .send(Err(io::Error::new(ErrorKind::Other, e)))
-                            .unwrap();
-                        break;
+                    ControlFlow::Break(())
+                }
+                Err(_) => ControlFlow::Break(()), // Upstream terminated without completing
+            },
+        )
+        .forget();
+        utils::receive(
+            &pool,
+            subscriber_rx,
+            "Flatten forwarding",
+            move |message| match message {
+                Ok(Notification::Next(message)) => {
+                    let _ = channel.send(Notification::Next(message));
+                    ControlFlow::Continue(())
+                }
+                Ok(Notification::Error(e)) => {
+                    let _ = channel.send(Notification::Error(e));
+                    ControlFlow::Break(())
+                }
+                Ok(Notification::Complete) => {
+                    if pending.fetch_sub(1, Ordering::AcqRel) == 1 {
+                        let _ = channel.send(Notification::Complete);
+                        return ControlFlow::Break(());
                     }
-                    Err(_) => break, // Channel closed
+                    ControlFlow::Continue(())
                 }
-            }
-            trace!("Flatten finished");
-        })
+                Err(_) => ControlFlow::Break(()), // Upstream terminated without completing
+            },
+        )
         .forget();
-        utils::forward_messages(subscriber_rx, channel, pool.clone());
         self.source.actual_subscribe(incoming_tx, pool);
     }
 }
@@ -60,7 +93,9 @@ mod tests {
     use crate::from_iter::from_iter;
     use crate::observable::Observable;
     use crate::observer::Observer;
+    use crate::subscriber::Subscriber;
     use futures::executor::ThreadPool;
+    use std::convert::Infallible;
     use std::sync::atomic::{AtomicI32, Ordering};
     use std::sync::Arc;
 
@@ -113,4 +148,20 @@ mod tests {
         futures::executor::block_on(handle);
         assert_eq!(collector_c.load(Ordering::Relaxed), 45);
     }
+
+    #[test]
+    fn it_completes_once_all_inner_observables_completed_themselves() {
+        let pool = ThreadPool::new().unwrap();
+        let handle = from_iter(0..2)
+            .flat_map(|v| {
+                create(move |s: Subscriber<i32, Infallible>| {
+                    s.next(v).unwrap();
+                    s.complete().unwrap();
+                })
+            })
+            .to_vec(pool);
+        let mut values = futures::executor::block_on(handle).unwrap();
+        values.sort();
+        assert_eq!(values, vec![0, 1]);
+    }
 }
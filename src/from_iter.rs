@@ -1,6 +1,9 @@
+use crate::notification::Notification;
 use crate::observable::Observable;
-use crate::scheduler::Scheduler;
-use std::sync::mpsc::Sender;
+use crate::scheduler::{LoopStep, Scheduler};
+use crate::subscriber::Subscriber;
+use log::trace;
+use std::convert::Infallible;
 
 pub struct FromIter<I> {
     iter: I,
@@ -16,18 +19,71 @@ where
 impl<I> Observable for FromIter<I>
 where
     I: IntoIterator,
+    I::IntoIter: Send + 'static,
     I::Item: Send + 'static,
 {
     type Item = I::Item;
+    type Error = Infallible;
 
-    fn actual_subscribe<O>(self, channel: Sender<std::io::Result<Self::Item>>, pool: O)
+    fn actual_subscribe<O>(self, channel: Subscriber<Self::Item, Self::Error>, pool: O)
     where
         O: Scheduler,
     {
-        self.iter.into_iter().for_each(|v| {
-            let channel_c = channel.clone();
-            pool.schedule(move || channel_c.send(Ok(v)).unwrap())
-                .forget();
-        });
+        let mut iter = self.iter.into_iter();
+        // One value per step, so that schedulers multiplexing loops on a thread stay responsive
+        pool.schedule_loop(move |_| match iter.next() {
+            Some(v) => match channel.send(Notification::Next(v)) {
+                Ok(()) => LoopStep::Received,
+                Err(_) => {
+                    trace!("FromIter stopped");
+                    LoopStep::Finished(()) // Unsubscribed
+                }
+            },
+            None => {
+                let _ = channel.send(Notification::Complete);
+                trace!("FromIter finished");
+                LoopStep::Finished(())
+            }
+        })
+        .forget();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::from_iter::from_iter;
+    use crate::observable::Observable;
+    use futures::executor::ThreadPool;
+    use std::sync::atomic::{AtomicI32, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    #[test]
+    fn it_emits_in_order() {
+        let collector = Arc::new(Mutex::new(vec![]));
+        let collector_c = collector.clone();
+        let handle = from_iter(0..100).subscribe(
+            move |v| collector.lock().unwrap().push(v),
+            ThreadPool::new().unwrap(),
+        );
+        futures::executor::block_on(handle);
+        assert_eq!(*collector_c.lock().unwrap(), (0..100).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn it_emits_infinite_iterators_lazily() {
+        let received = Arc::new(AtomicI32::new(0));
+        let received_c = received.clone();
+        let subscription = from_iter(0..).bounded(4).map_inline(|v: i32| v).subscribe(
+            move |_| {
+                received_c.fetch_add(1, Ordering::Relaxed);
+            },
+            ThreadPool::new().unwrap(),
+        );
+        while received.load(Ordering::Relaxed) < 100 {
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        subscription.unsubscribe();
+        futures::executor::block_on(subscription);
     }
 }
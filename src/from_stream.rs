@@ -0,0 +1,130 @@
+use crate::notification::Notification;
+use crate::observable::Observable;
+use crate::scheduler::{LoopStep, Scheduler, Wait};
+use crate::subscriber::Subscriber;
+use futures::task::ArcWake;
+use futures::{FutureExt, Stream, StreamExt};
+use log::trace;
+use std::convert::Infallible;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::thread::{self, Thread};
+
+pub struct FromStream<St> {
+    stream: St,
+}
+
+pub fn from_stream<St>(stream: St) -> FromStream<St>
+where
+    St: Stream,
+{
+    FromStream { stream }
+}
+
+impl<St> Observable for FromStream<St>
+where
+    St: Stream + Send + 'static,
+    St::Item: Send + 'static,
+{
+    type Item = St::Item;
+    type Error = Infallible;
+
+    fn actual_subscribe<O>(self, channel: Subscriber<Self::Item, Self::Error>, pool: O)
+    where
+        O: Scheduler,
+    {
+        let mut stream = Box::pin(self.stream);
+        pool.schedule_loop(move |wait| {
+            let next = match wait {
+                Wait::Block => wait_for_next(&mut stream),
+                Wait::Poll => match stream.next().now_or_never() {
+                    Some(next) => next,
+                    None => return LoopStep::Empty,
+                },
+            };
+            match next {
+                Some(v) => match channel.send(Notification::Next(v)) {
+                    Ok(()) => LoopStep::Received,
+                    Err(_) => {
+                        trace!("FromStream stopped");
+                        LoopStep::Finished(()) // Unsubscribed
+                    }
+                },
+                None => {
+                    let _ = channel.send(Notification::Complete);
+                    trace!("FromStream finished");
+                    LoopStep::Finished(())
+                }
+            }
+        })
+        .forget();
+    }
+}
+
+/// Parks the thread until the stream has its next value, as `block_on` can't be used within executors like the `ThreadPool`
+fn wait_for_next<St>(stream: &mut Pin<Box<St>>) -> Option<St::Item>
+where
+    St: Stream,
+{
+    let waker = futures::task::waker(Arc::new(Unpark(thread::current())));
+    let mut context = Context::from_waker(&waker);
+    loop {
+        if let Poll::Ready(next) = stream.poll_next_unpin(&mut context) {
+            return next;
+        }
+        thread::park();
+    }
+}
+
+struct Unpark(Thread);
+
+impl ArcWake for Unpark {
+    fn wake_by_ref(arc_self: &Arc<Self>) {
+        arc_self.0.unpark();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::current_thread_scheduler::CurrentThreadScheduler;
+    use crate::from_stream::from_stream;
+    use crate::observable::Observable;
+    use futures::executor::ThreadPool;
+    use futures::StreamExt;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn it_emits_streams() {
+        let collector = Arc::new(Mutex::new(vec![]));
+        let collector_c = collector.clone();
+        let subscription = from_stream(futures::stream::iter(0..5).then(|v| async move { v }))
+            .map_inline(|v| v * 2)
+            .subscribe(
+                move |v| collector_c.lock().unwrap().push(v),
+                ThreadPool::new().unwrap(),
+            );
+        futures::executor::block_on(subscription);
+        assert_eq!(*collector.lock().unwrap(), vec![0, 2, 4, 6, 8]);
+    }
+
+    #[test]
+    fn it_polls_streams_on_the_current_thread() {
+        let scheduler = CurrentThreadScheduler::new();
+        let (tx, rx) = futures::channel::mpsc::unbounded();
+        let collector = Arc::new(Mutex::new(vec![]));
+        let collector_c = collector.clone();
+        let subscription = from_stream(rx).subscribe(
+            move |v| collector_c.lock().unwrap().push(v),
+            scheduler.clone(),
+        );
+        scheduler.run();
+        assert!(collector.lock().unwrap().is_empty());
+        tx.unbounded_send(1).unwrap();
+        tx.unbounded_send(2).unwrap();
+        drop(tx);
+        scheduler.run();
+        futures::executor::block_on(subscription);
+        assert_eq!(*collector.lock().unwrap(), vec![1, 2]);
+    }
+}
@@ -1,28 +1,29 @@
+use crate::notification::Notification;
 use crate::observable::Observable;
 use crate::scheduler::Scheduler;
+use crate::subscriber::Subscriber;
 use crate::utils;
-use log::{error, trace};
 use std::collections::HashMap;
 use std::hash::Hash;
-use std::io;
-use std::io::ErrorKind;
-use std::sync::mpsc;
-use std::sync::mpsc::{Receiver, Sender};
+use std::ops::ControlFlow;
+use std::sync::mpsc::Receiver;
 
-pub type SenderMap<Key, Item> = HashMap<Key, Sender<io::Result<Item>>>;
+pub type SenderMap<Key, Item, E> = HashMap<Key, Subscriber<Item, E>>;
 
-pub struct KeySubject<Key, Item> {
+pub struct KeySubject<Key, Item, E> {
     pub key: Key,
-    pub(crate) source: Receiver<io::Result<Item>>,
+    pub(crate) source: Receiver<Notification<Item, E>>,
 }
 
-impl<Key, Item> Observable for KeySubject<Key, Item>
+impl<Key, Item, E> Observable for KeySubject<Key, Item, E>
 where
     Item: Send + 'static,
+    E: Send + 'static,
 {
     type Item = Item;
+    type Error = E;
 
-    fn actual_subscribe<O>(self, channel: Sender<io::Result<Self::Item>>, pool: O)
+    fn actual_subscribe<O>(self, channel: Subscriber<Self::Item, Self::Error>, pool: O)
     where
         O: Scheduler + Clone + Send + 'static,
     {
@@ -37,51 +38,58 @@ pub struct GroupByOp<Source, GroupingFunction, CS> {
 }
 
 impl<Source, GroupingFunction, Key> Observable
-    for GroupByOp<Source, GroupingFunction, SenderMap<Key, Source::Item>>
+    for GroupByOp<Source, GroupingFunction, SenderMap<Key, Source::Item, Source::Error>>
 where
     Source: Observable,
     Source::Item: Send + 'static,
+    Source::Error: Send + 'static,
     GroupingFunction: Fn(&Source::Item) -> Key + Send + 'static,
     Key: Send + 'static + Hash + Eq + Clone,
 {
-    type Item = KeySubject<Key, Source::Item>;
+    type Item = KeySubject<Key, Source::Item, Source::Error>;
+    type Error = Source::Error;
 
-    fn actual_subscribe<O>(mut self, channel: Sender<io::Result<Self::Item>>, pool: O)
+    fn actual_subscribe<O>(self, channel: Subscriber<Self::Item, Self::Error>, pool: O)
     where
         O: Scheduler + Clone + Send + 'static,
     {
-        let (incoming_tx, incoming_rx) = mpsc::channel::<io::Result<Source::Item>>();
-        pool.schedule(move || {
-            loop {
-                let message = incoming_rx.recv();
-                match message {
-                    Ok(Ok(message)) => {
-                        let key = (self.grouping_function)(&message);
-                        let sender = self.channel_store.entry(key.clone()).or_insert_with(|| {
-                            let (subject_tx, subject_rx) =
-                                mpsc::channel::<io::Result<Source::Item>>();
-                            channel
-                                .send(Ok(KeySubject {
-                                    key,
-                                    source: subject_rx,
-                                }))
-                                .unwrap();
-                            subject_tx
-                        });
-                        sender.send(Ok(message)).unwrap();
-                    }
-                    Ok(Err(e)) => {
-                        error!("Group By, inner unwrap: {:?}", e.to_string());
-                        channel
-                            .send(Err(io::Error::new(ErrorKind::Other, e)))
-                            .unwrap();
-                        break;
+        let (incoming_tx, incoming_rx) = channel.upstream::<Source::Item, Source::Error>();
+        let grouping_function = self.grouping_function;
+        let mut channel_store = self.channel_store;
+        utils::receive(
+            &pool,
+            incoming_rx,
+            "Group by",
+            move |message| match message {
+                Ok(Notification::Next(_)) if channel.is_closed() => ControlFlow::Break(()),
+                Ok(Notification::Next(message)) => {
+                    let key = (grouping_function)(&message);
+                    let sender = channel_store.entry(key.clone()).or_insert_with(|| {
+                        let (subject_tx, subject_rx) =
+                            channel.upstream::<Source::Item, Source::Error>();
+                        let _ = channel.send(Notification::Next(KeySubject {
+                            key,
+                            source: subject_rx,
+                        }));
+                        subject_tx
+                    });
+                    let _ = sender.send(Notification::Next(message));
+                    ControlFlow::Continue(())
+                }
+                Ok(Notification::Error(e)) => {
+                    let _ = channel.send(Notification::Error(e));
+                    ControlFlow::Break(())
+                }
+                Ok(Notification::Complete) => {
+                    for sender in channel_store.values() {
+                        let _ = sender.send(Notification::Complete);
                     }
-                    Err(_) => break, // Channel closed
+                    let _ = channel.send(Notification::Complete);
+                    ControlFlow::Break(())
                 }
-            }
-            trace!("Group by finished");
-        })
+                Err(_) => ControlFlow::Break(()), // Upstream terminated without completing
+            },
+        )
         .forget();
         self.source.actual_subscribe(incoming_tx, pool);
     }
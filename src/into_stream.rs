@@ -0,0 +1,81 @@
+use crate::observable::Observable;
+use crate::scheduler::Scheduler;
+use crate::subscription::Subscription;
+use futures::channel::mpsc::{self, UnboundedReceiver};
+use futures::Stream;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// The values of a pipeline as `futures::Stream`, which ends after the terminating error or completion.
+/// Unsubscribes from the pipeline once dropped.
+pub struct ObservableStream<Item, E> {
+    incoming: UnboundedReceiver<Result<Item, E>>,
+    subscription: Subscription,
+}
+
+impl<Item, E> ObservableStream<Item, E>
+where
+    Item: Send + 'static,
+    E: Send + 'static,
+{
+    pub(crate) fn new<S, O>(source: S, scheduler: O) -> Self
+    where
+        S: Observable<Item = Item, Error = E>,
+        O: Scheduler + Clone + Send + 'static,
+    {
+        let (outgoing, incoming) = mpsc::unbounded();
+        let outgoing_c = outgoing.clone();
+        let subscription = source.subscribe_with(
+            move |v| {
+                let _ = outgoing.unbounded_send(Ok(v));
+            },
+            move |e| {
+                let _ = outgoing_c.unbounded_send(Err(e));
+            },
+            || {}, // Dropping the senders ends the stream
+            scheduler,
+        );
+        ObservableStream {
+            incoming,
+            subscription,
+        }
+    }
+}
+
+impl<Item, E> Stream for ObservableStream<Item, E> {
+    type Item = Result<Item, E>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Pin::new(&mut self.get_mut().incoming).poll_next(cx)
+    }
+}
+
+impl<Item, E> Drop for ObservableStream<Item, E> {
+    fn drop(&mut self) {
+        self.subscription.unsubscribe();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::create::create;
+    use crate::observable::Observable;
+    use crate::observer::Observer;
+    use crate::subscriber::Subscriber;
+    use futures::executor::ThreadPool;
+    use futures::StreamExt;
+
+    #[test]
+    fn it_streams_values() {
+        let values = futures::executor::block_on(
+            create(|sender: Subscriber<i32, String>| {
+                sender.next(1).unwrap();
+                sender.next(2).unwrap();
+                sender.error("error".to_string()).unwrap();
+            })
+            .into_stream(ThreadPool::new().unwrap())
+            .collect::<Vec<_>>(),
+        );
+        assert_eq!(values, vec![Ok(1), Ok(2), Err("error".to_string())]);
+    }
+}
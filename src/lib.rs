@@ -2,18 +2,43 @@ extern crate core;
 
 #[cfg(feature = "math")]
 pub mod average;
+pub mod blocking_iter;
+pub mod bounded;
+pub mod boxed;
+pub mod clock;
+mod collect;
 pub mod create;
+pub mod current_thread_scheduler;
+mod deferred_scheduler;
+pub mod dyn_scheduler;
 pub mod filter;
 pub mod flatten;
 pub mod from_iter;
+pub mod from_stream;
 pub mod group_by;
+pub mod into_stream;
 pub mod map;
+pub mod map_err;
+pub mod marble;
 pub mod merge;
+pub mod new_thread_scheduler;
+pub mod notification;
 pub mod observable;
+pub mod observe_on;
 pub mod observer;
+pub mod ordered;
+pub mod publish;
+#[cfg(feature = "rayon")]
+pub mod rayon_scheduler;
 pub mod reduce;
 pub mod scheduler;
-#[cfg(feature = "recurring")]
 pub mod sliding_window;
+pub mod subject;
 pub mod subscribe_on;
+pub mod subscriber;
+pub mod subscription;
+pub mod test_scheduler;
+mod timer;
+#[cfg(feature = "tokio")]
+pub mod tokio_scheduler;
 pub mod utils;
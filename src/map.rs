@@ -1,12 +1,13 @@
+use crate::notification::Notification;
 use crate::observable::Observable;
-use std::io;
-use std::io::ErrorKind;
 
 use crate::scheduler::Scheduler;
+use crate::subscriber::Subscriber;
+use crate::utils;
+use crate::utils::{Completion, Permits};
 
-use log::{error, trace};
-use std::sync::mpsc;
-use std::sync::mpsc::Sender;
+use std::ops::ControlFlow;
+use std::sync::Arc;
 
 pub struct MapOp<S, M> {
     pub(crate) source: S,
@@ -17,44 +18,103 @@ impl<Item, S, M> Observable for MapOp<S, M>
 where
     S: Observable,
     S::Item: Send + 'static,
+    S::Error: Send + 'static,
     M: Fn(S::Item) -> Item + Clone + Send + 'static,
     Item: Send + 'static,
 {
     type Item = Item;
+    type Error = S::Error;
 
-    fn actual_subscribe<O>(self, channel: Sender<io::Result<Self::Item>>, pool: O)
+    fn actual_subscribe<O>(self, channel: Subscriber<Self::Item, Self::Error>, pool: O)
     where
         O: Scheduler + Clone + Send + 'static,
     {
-        let (incoming_tx, incoming_rx) = mpsc::channel::<io::Result<S::Item>>();
+        let (incoming_tx, incoming_rx) = channel.upstream::<S::Item, S::Error>();
         let pool_c = pool.clone();
-        pool.schedule(move || {
-            loop {
-                let message = incoming_rx.recv();
-                let channel_c = channel.clone();
-                let func_c = self.func.clone();
-                let pool_cc = pool_c.clone();
-                match message {
-                    Ok(Ok(message)) => {
-                        pool_cc
-                            .schedule(move || {
-                                let out = (func_c)(message);
-                                channel_c.send(Ok(out)).unwrap();
-                            })
-                            .forget();
-                    }
-                    Ok(Err(e)) => {
-                        error!("Map, inner unwrap: {:?}", e.to_string());
-                        channel
-                            .send(Err(io::Error::new(ErrorKind::Other, e)))
-                            .unwrap();
-                        break;
+        let completion = Arc::new(Completion::new(channel.clone()));
+        let permits = channel
+            .capacity()
+            .map(|capacity| Arc::new(Permits::new(capacity)));
+        utils::receive_with_permits(
+            &pool,
+            incoming_rx,
+            permits,
+            "Map",
+            move |message, permit| match message {
+                Ok(Notification::Next(_)) if channel.is_closed() => ControlFlow::Break(()),
+                Ok(Notification::Next(message)) => {
+                    let channel_c = channel.clone();
+                    let func_c = self.func.clone();
+                    let completion_c = completion.clone();
+                    pool_c.schedule_detached(move || {
+                        let out = (func_c)(message);
+                        let _ = channel_c.send(Notification::Next(out));
+                        drop(permit);
+                        drop(completion_c);
+                    });
+                    ControlFlow::Continue(())
+                }
+                Ok(Notification::Error(e)) => {
+                    let _ = channel.send(Notification::Error(e));
+                    ControlFlow::Break(())
+                }
+                Ok(Notification::Complete) => {
+                    completion.complete();
+                    ControlFlow::Break(())
+                }
+                Err(_) => ControlFlow::Break(()), // Upstream terminated without completing
+            },
+        )
+        .forget();
+        self.source.actual_subscribe(incoming_tx, pool);
+    }
+}
+
+/// Applies the function on the operator's own loop, without scheduling a task per value
+pub struct MapInlineOp<S, M> {
+    pub(crate) source: S,
+    pub(crate) func: M,
+}
+
+impl<Item, S, M> Observable for MapInlineOp<S, M>
+where
+    S: Observable,
+    S::Item: Send + 'static,
+    S::Error: Send + 'static,
+    M: Fn(S::Item) -> Item + Send + 'static,
+    Item: Send + 'static,
+{
+    type Item = Item;
+    type Error = S::Error;
+
+    fn actual_subscribe<O>(self, channel: Subscriber<Self::Item, Self::Error>, pool: O)
+    where
+        O: Scheduler + Clone + Send + 'static,
+    {
+        let (incoming_tx, incoming_rx) = channel.upstream::<S::Item, S::Error>();
+        let func = self.func;
+        utils::receive(
+            &pool,
+            incoming_rx,
+            "MapInline",
+            move |message| match message {
+                Ok(Notification::Next(message)) => {
+                    match channel.send(Notification::Next((func)(message))) {
+                        Ok(()) => ControlFlow::Continue(()),
+                        Err(_) => ControlFlow::Break(()),
                     }
-                    Err(_) => break, // Channel closed
                 }
-            }
-            trace!("Map finished");
-        })
+                Ok(Notification::Error(e)) => {
+                    let _ = channel.send(Notification::Error(e));
+                    ControlFlow::Break(())
+                }
+                Ok(Notification::Complete) => {
+                    let _ = channel.send(Notification::Complete);
+                    ControlFlow::Break(())
+                }
+                Err(_) => ControlFlow::Break(()), // Upstream terminated without completing
+            },
+        )
         .forget();
         self.source.actual_subscribe(incoming_tx, pool);
     }
@@ -82,4 +142,18 @@ mod tests {
         futures::executor::block_on(handle);
         assert_eq!(collector_c.load(Ordering::Relaxed), 55);
     }
+
+    #[test]
+    fn it_maps_inline() {
+        let collector = Arc::new(AtomicI32::new(0));
+        let collector_c = collector.clone();
+        let handle = from_iter(0..10).map_inline(|v| v + 1).subscribe(
+            move |v| {
+                collector.fetch_add(v, Ordering::Relaxed);
+            },
+            ThreadPool::new().unwrap(),
+        );
+        futures::executor::block_on(handle);
+        assert_eq!(collector_c.load(Ordering::Relaxed), 55);
+    }
 }
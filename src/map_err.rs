@@ -0,0 +1,82 @@
+use crate::notification::Notification;
+use crate::observable::Observable;
+use crate::scheduler::Scheduler;
+use crate::subscriber::Subscriber;
+use crate::utils;
+use std::ops::ControlFlow;
+
+pub struct MapErrOp<S, F> {
+    pub(crate) source: S,
+    pub(crate) func: F,
+}
+
+impl<E, S, F> Observable for MapErrOp<S, F>
+where
+    S: Observable,
+    S::Item: Send + 'static,
+    S::Error: Send + 'static,
+    F: Fn(S::Error) -> E + Send + 'static,
+    E: Send + 'static,
+{
+    type Item = S::Item;
+    type Error = E;
+
+    fn actual_subscribe<O>(self, channel: Subscriber<Self::Item, Self::Error>, pool: O)
+    where
+        O: Scheduler + Clone + Send + 'static,
+    {
+        let (incoming_tx, incoming_rx) = channel.upstream::<S::Item, S::Error>();
+        let func = self.func;
+        utils::receive(
+            &pool,
+            incoming_rx,
+            "Map err",
+            move |message| match message {
+                Ok(Notification::Next(message)) => {
+                    match channel.send(Notification::Next(message)) {
+                        Ok(()) => ControlFlow::Continue(()),
+                        Err(_) => ControlFlow::Break(()),
+                    }
+                }
+                Ok(Notification::Error(e)) => {
+                    let _ = channel.send(Notification::Error((func)(e)));
+                    ControlFlow::Break(())
+                }
+                Ok(Notification::Complete) => {
+                    let _ = channel.send(Notification::Complete);
+                    ControlFlow::Break(())
+                }
+                Err(_) => ControlFlow::Break(()), // Upstream terminated without completing
+            },
+        )
+        .forget();
+        self.source.actual_subscribe(incoming_tx, pool);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::create::create;
+    use crate::observable::Observable;
+    use crate::observer::Observer;
+    use crate::subscriber::Subscriber;
+    use crate::subscription::Terminated;
+    use futures::executor::ThreadPool;
+
+    #[derive(Debug, PartialEq)]
+    struct DomainError(usize);
+
+    #[test]
+    fn it_maps_errors() {
+        let handle = create(|sender: Subscriber<i32, String>| {
+            sender.next(1).unwrap();
+            sender.error("failed".to_string()).unwrap();
+        })
+        .map_err(|e| DomainError(e.len()))
+        .try_subscribe(|v| assert_eq!(v, 1), ThreadPool::new().unwrap());
+        assert_eq!(
+            futures::executor::block_on(handle),
+            Err(Terminated::Error(DomainError(6)))
+        );
+    }
+}
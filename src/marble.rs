@@ -0,0 +1,293 @@
+use crate::notification::Notification;
+use crate::observable::Observable;
+use crate::scheduler::Scheduler;
+use crate::subscriber::Subscriber;
+use crate::test_scheduler::TestScheduler;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// The virtual time every frame of a marble diagram takes
+pub const FRAME: Duration = Duration::from_millis(1);
+
+/// Recording stops after this many frames if the observable did not terminate until then
+const MAX_FRAMES: u32 = 1000;
+
+/// A notification and the frame it was emitted in
+pub type Recorded<Item, E> = (usize, Notification<Item, E>);
+
+/// Parses a marble diagram, in which
+/// - `-` lets one frame pass,
+/// - `|` completes and `#` errors with `error`,
+/// - `(ab)` emits all notifications in the parenthesis in the same frame,
+/// - spaces are ignored,
+/// - every other character emits `value(character)`.
+///
+/// Every notification, or group of notifications, takes one frame.
+pub fn parse_with<Item, E>(
+    marbles: &str,
+    value: impl Fn(char) -> Item,
+    error: E,
+) -> Vec<Recorded<Item, E>>
+where
+    E: Clone,
+{
+    let mut recorded = vec![];
+    let mut frame = 0;
+    let mut grouped = false;
+    for character in marbles.chars() {
+        match character {
+            ' ' => continue,
+            '-' => {}
+            '(' => {
+                grouped = true;
+                continue;
+            }
+            ')' => grouped = false,
+            '|' => recorded.push((frame, Notification::Complete)),
+            '#' => recorded.push((frame, Notification::Error(error.clone()))),
+            character => recorded.push((frame, Notification::Next(value(character)))),
+        }
+        if !grouped {
+            frame += 1;
+        }
+    }
+    recorded
+}
+
+/// Parses a marble diagram whose values are its characters, erroring with `"error"`
+pub fn parse(marbles: &str) -> Vec<Recorded<char, String>> {
+    parse_with(marbles, |character| character, "error".to_string())
+}
+
+/// Creates test observables from marble diagrams and records what pipelines built from them emit,
+/// all on one `TestScheduler`.
+#[derive(Clone, Default)]
+pub struct MarbleTest {
+    scheduler: TestScheduler,
+}
+
+impl MarbleTest {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn scheduler(&self) -> TestScheduler {
+        self.scheduler.clone()
+    }
+
+    /// An observable which emits the diagram, starting when it is subscribed to
+    pub fn cold(&self, marbles: &str) -> ColdObservable<char, String> {
+        self.cold_notifications(parse(marbles))
+    }
+
+    pub fn cold_with<Item, E>(
+        &self,
+        marbles: &str,
+        value: impl Fn(char) -> Item,
+        error: E,
+    ) -> ColdObservable<Item, E>
+    where
+        E: Clone,
+    {
+        self.cold_notifications(parse_with(marbles, value, error))
+    }
+
+    fn cold_notifications<Item, E>(
+        &self,
+        notifications: Vec<Recorded<Item, E>>,
+    ) -> ColdObservable<Item, E> {
+        ColdObservable {
+            scheduler: self.scheduler.clone(),
+            notifications,
+        }
+    }
+
+    /// An observable which emits the diagram starting now, to whoever is subscribed at that time
+    pub fn hot(&self, marbles: &str) -> HotObservable<char, String> {
+        self.hot_notifications(parse(marbles))
+    }
+
+    pub fn hot_with<Item, E>(
+        &self,
+        marbles: &str,
+        value: impl Fn(char) -> Item,
+        error: E,
+    ) -> HotObservable<Item, E>
+    where
+        Item: Clone + Send + 'static,
+        E: Clone + Send + 'static,
+    {
+        self.hot_notifications(parse_with(marbles, value, error))
+    }
+
+    fn hot_notifications<Item, E>(
+        &self,
+        notifications: Vec<Recorded<Item, E>>,
+    ) -> HotObservable<Item, E>
+    where
+        Item: Clone + Send + 'static,
+        E: Clone + Send + 'static,
+    {
+        let hot = HotObservable {
+            subscribers: Arc::new(Mutex::new(vec![])),
+        };
+        let start = self.scheduler.now();
+        for (frame, notification) in notifications {
+            let subscribers = hot.subscribers.clone();
+            self.scheduler
+                .schedule_timer(start + FRAME * frame as u32, None, move || {
+                    for subscriber in subscribers.lock().unwrap().iter() {
+                        let _ = subscriber.send(notification.clone());
+                    }
+                });
+        }
+        hot
+    }
+
+    /// Subscribes to the observable and advances the clock until it terminated,
+    /// returning its notifications with the frames since the subscription they arrived in
+    pub fn record<O>(&self, observable: O) -> Vec<Recorded<O::Item, O::Error>>
+    where
+        O: Observable,
+        O::Item: Send + 'static,
+        O::Error: Send + 'static,
+    {
+        let start = self.scheduler.now();
+        let recorded = Arc::new(Mutex::new(vec![]));
+        let (next, error, complete) = (recorded.clone(), recorded.clone(), recorded.clone());
+        let (next_scheduler, error_scheduler, complete_scheduler) = (
+            self.scheduler.clone(),
+            self.scheduler.clone(),
+            self.scheduler.clone(),
+        );
+        let subscription = observable.subscribe_with(
+            move |v| {
+                let frame = frame(&next_scheduler, start);
+                next.lock().unwrap().push((frame, Notification::Next(v)))
+            },
+            move |e| {
+                let frame = frame(&error_scheduler, start);
+                error.lock().unwrap().push((frame, Notification::Error(e)))
+            },
+            move || {
+                let frame = frame(&complete_scheduler, start);
+                complete
+                    .lock()
+                    .unwrap()
+                    .push((frame, Notification::Complete))
+            },
+            self.scheduler.clone(),
+        );
+        let end = start + FRAME * MAX_FRAMES;
+        self.scheduler.advance_by(Duration::ZERO);
+        while !subscription.is_closed() && self.scheduler.now() < end {
+            self.scheduler.advance_by(FRAME);
+        }
+        subscription.unsubscribe();
+        self.scheduler.run();
+        let mut recorded = recorded.lock().unwrap();
+        std::mem::take(&mut *recorded)
+    }
+
+    /// Asserts that the observable emits the given diagram, counting frames from the subscription
+    pub fn expect<O>(&self, observable: O, marbles: &str)
+    where
+        O: Observable<Item = char, Error = String>,
+    {
+        assert_eq!(self.record(observable), parse(marbles));
+    }
+}
+
+fn frame(scheduler: &TestScheduler, start: Duration) -> usize {
+    ((scheduler.now() - start).as_nanos() / FRAME.as_nanos()) as usize
+}
+
+pub struct ColdObservable<Item, E> {
+    scheduler: TestScheduler,
+    notifications: Vec<Recorded<Item, E>>,
+}
+
+impl<Item, E> Observable for ColdObservable<Item, E>
+where
+    Item: Send + 'static,
+    E: Send + 'static,
+{
+    type Item = Item;
+    type Error = E;
+
+    fn actual_subscribe<O>(self, channel: Subscriber<Self::Item, Self::Error>, _: O)
+    where
+        O: Scheduler + Clone + Send + 'static,
+    {
+        let start = self.scheduler.now();
+        for (frame, notification) in self.notifications {
+            let channel_c = channel.clone();
+            let mut notification = Some(notification);
+            self.scheduler
+                .schedule_timer(start + FRAME * frame as u32, None, move || {
+                    if let Some(notification) = notification.take() {
+                        let _ = channel_c.send(notification);
+                    }
+                });
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct HotObservable<Item, E> {
+    subscribers: Arc<Mutex<Vec<Subscriber<Item, E>>>>,
+}
+
+impl<Item, E> Observable for HotObservable<Item, E>
+where
+    Item: Send + 'static,
+    E: Send + 'static,
+{
+    type Item = Item;
+    type Error = E;
+
+    fn actual_subscribe<O>(self, channel: Subscriber<Self::Item, Self::Error>, _: O)
+    where
+        O: Scheduler + Clone + Send + 'static,
+    {
+        self.subscribers.lock().unwrap().push(channel);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::marble::{parse, MarbleTest, FRAME};
+    use crate::notification::Notification;
+    use crate::observable::Observable;
+
+    #[test]
+    fn it_parses_marbles() {
+        assert_eq!(
+            parse("-a-(bc)|"),
+            vec![
+                (1, Notification::Next('a')),
+                (3, Notification::Next('b')),
+                (3, Notification::Next('c')),
+                (4, Notification::Complete)
+            ]
+        );
+    }
+
+    #[test]
+    fn it_tests_operators() {
+        let test = MarbleTest::new();
+        test.expect(
+            test.cold("-a-b-|").map(|c| c.to_ascii_uppercase()),
+            "-A-B-|",
+        );
+        test.expect(test.cold("-a-b-#").filter(|c| *c != 'a'), "---b-#");
+    }
+
+    #[test]
+    fn it_tests_hot_observables() {
+        let test = MarbleTest::new();
+        let hot = test.hot("-a--b--|");
+        test.scheduler().advance_by(FRAME * 2);
+        test.expect(hot.merge(test.cold("-c|")), "-cb--|");
+    }
+}
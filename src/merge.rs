@@ -1,34 +1,123 @@
+use crate::notification::Notification;
 use crate::observable::Observable;
 use crate::scheduler::Scheduler;
-use std::io;
-use std::sync::mpsc::Sender;
+use crate::subscriber::Subscriber;
+use crate::utils;
+use std::ops::ControlFlow;
+use std::sync::mpsc::Receiver;
 
 pub struct MergeObservable<Source1, Source2> {
     pub(crate) source1: Source1,
     pub(crate) source2: Source2,
 }
 
-impl<Source> Observable for MergeObservable<Source, Source>
+pub struct MergeAllObservable<Source> {
+    sources: Vec<Source>,
+}
+
+/// Merges the values of all given observables into one stream, completing once all of them completed
+pub fn merge_all<I>(sources: I) -> MergeAllObservable<I::Item>
+where
+    I: IntoIterator,
+    I::Item: Observable,
+{
+    MergeAllObservable {
+        sources: sources.into_iter().collect(),
+    }
+}
+
+impl<Source1, Source2> Observable for MergeObservable<Source1, Source2>
+where
+    Source1: Observable,
+    Source1::Item: Send + 'static,
+    Source1::Error: Send + 'static,
+    Source2: Observable<Item = Source1::Item, Error = Source1::Error>,
+{
+    type Item = Source1::Item;
+    type Error = Source1::Error;
+
+    fn actual_subscribe<O>(self, channel: Subscriber<Self::Item, Self::Error>, pool: O)
+    where
+        O: Scheduler + Clone + Send + 'static,
+    {
+        let (incoming_tx, incoming_rx) = channel.upstream::<Source1::Item, Source1::Error>();
+        merge_messages(incoming_rx, channel, 2, pool.clone());
+        // Every source gets a subscriber of its own, so a source terminating twice is only counted once
+        self.source1
+            .actual_subscribe(incoming_tx.fork(), pool.clone());
+        self.source2.actual_subscribe(incoming_tx, pool);
+    }
+}
+
+impl<Source> Observable for MergeAllObservable<Source>
 where
     Source: Observable,
     Source::Item: Send + 'static,
+    Source::Error: Send + 'static,
 {
     type Item = Source::Item;
+    type Error = Source::Error;
 
-    fn actual_subscribe<O>(self, channel: Sender<io::Result<Self::Item>>, pool: O)
+    fn actual_subscribe<O>(self, channel: Subscriber<Self::Item, Self::Error>, pool: O)
     where
         O: Scheduler + Clone + Send + 'static,
     {
-        self.source1.actual_subscribe(channel.clone(), pool.clone());
-        self.source2.actual_subscribe(channel, pool);
+        let (incoming_tx, incoming_rx) = channel.upstream::<Source::Item, Source::Error>();
+        merge_messages(incoming_rx, channel, self.sources.len(), pool.clone());
+        for source in self.sources {
+            source.actual_subscribe(incoming_tx.fork(), pool.clone());
+        }
+    }
+}
+
+/// Forwards the values of `sources` upstream observables sharing `incoming`, completing once all of them completed
+fn merge_messages<Item, E, O>(
+    incoming: Receiver<Notification<Item, E>>,
+    channel: Subscriber<Item, E>,
+    sources: usize,
+    pool: O,
+) where
+    Item: Send + 'static,
+    E: Send + 'static,
+    O: Scheduler,
+{
+    if sources == 0 {
+        let _ = channel.send(Notification::Complete);
+        return;
     }
+    let mut completed = 0;
+    utils::receive(&pool, incoming, "Merge", move |message| match message {
+        Ok(Notification::Next(message)) => match channel.send(Notification::Next(message)) {
+            Ok(()) => ControlFlow::Continue(()),
+            Err(_) => ControlFlow::Break(()),
+        },
+        Ok(Notification::Error(e)) => {
+            let _ = channel.send(Notification::Error(e));
+            ControlFlow::Break(())
+        }
+        Ok(Notification::Complete) => {
+            completed += 1;
+            if completed == sources {
+                let _ = channel.send(Notification::Complete);
+                return ControlFlow::Break(());
+            }
+            ControlFlow::Continue(())
+        }
+        Err(_) => ControlFlow::Break(()), // Upstream terminated without completing
+    })
+    .forget();
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::create::create;
     use crate::from_iter::from_iter;
+    use crate::merge::merge_all;
     use crate::observable::Observable;
+    use crate::observer::Observer;
+    use crate::subscriber::Subscriber;
     use futures::executor::ThreadPool;
+    use std::convert::Infallible;
     use std::sync::atomic::{AtomicI32, Ordering};
     use std::sync::Arc;
 
@@ -45,4 +134,52 @@ mod tests {
         futures::executor::block_on(handle);
         assert_eq!(collector_c.load(Ordering::Relaxed), 45);
     }
+
+    #[test]
+    fn it_merges_different_observables() {
+        let collector = Arc::new(AtomicI32::new(0));
+        let collector_c = collector.clone();
+        let handle = from_iter(0..5)
+            .map(|v| v * 2)
+            .merge(create(|sender: Subscriber<i32, Infallible>| {
+                sender.next(100).unwrap();
+            }))
+            .subscribe(
+                move |v| {
+                    collector.fetch_add(v, Ordering::Relaxed);
+                },
+                ThreadPool::new().unwrap(),
+            );
+        futures::executor::block_on(handle);
+        assert_eq!(collector_c.load(Ordering::Relaxed), 120);
+    }
+
+    #[test]
+    fn it_merges_all() {
+        let collector = Arc::new(AtomicI32::new(0));
+        let collector_c = collector.clone();
+        let handle = merge_all((0..4).map(|i| from_iter(i * 10..(i + 1) * 10))).subscribe(
+            move |v| {
+                collector.fetch_add(v, Ordering::Relaxed);
+            },
+            ThreadPool::new().unwrap(),
+        );
+        futures::executor::block_on(handle);
+        assert_eq!(collector_c.load(Ordering::Relaxed), 780);
+    }
+
+    #[test]
+    fn it_completes_once_all_sources_completed_themselves() {
+        let pool = ThreadPool::new().unwrap();
+        let source = |v: i32| {
+            create(move |sender: Subscriber<i32, Infallible>| {
+                sender.next(v).unwrap();
+                sender.complete().unwrap();
+            })
+        };
+        let mut values =
+            futures::executor::block_on(source(1).merge(source(2)).to_vec(pool)).unwrap();
+        values.sort();
+        assert_eq!(values, vec![1, 2]);
+    }
 }
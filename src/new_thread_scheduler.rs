@@ -0,0 +1,130 @@
+use crate::scheduler::Scheduler;
+use crate::timer;
+use futures::future::{AbortHandle, RemoteHandle};
+use futures::FutureExt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Runs every scheduled task on an OS thread of its own, so producers blocking on IO
+/// don't tie up the workers of a pool the rest of the pipeline runs on.
+#[derive(Clone, Default)]
+pub struct NewThreadScheduler {
+    name_prefix: Option<String>,
+    spawned: Arc<AtomicUsize>,
+}
+
+impl NewThreadScheduler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Names the threads `{name_prefix}{index}`, like `ThreadPoolBuilder::name_prefix`
+    pub fn named(name_prefix: impl Into<String>) -> Self {
+        Self {
+            name_prefix: Some(name_prefix.into()),
+            spawned: Arc::default(),
+        }
+    }
+
+    fn spawn(&self, task: impl FnOnce() + Send + 'static) {
+        let mut builder = thread::Builder::new();
+        if let Some(name_prefix) = &self.name_prefix {
+            let index = self.spawned.fetch_add(1, Ordering::Relaxed);
+            builder = builder.name(format!("{}{}", name_prefix, index));
+        }
+        builder.spawn(task).expect("failed to spawn thread");
+    }
+}
+
+impl Scheduler for NewThreadScheduler {
+    fn schedule<R>(&self, task: impl FnOnce() -> R + Send + 'static) -> RemoteHandle<R>
+    where
+        R: Send + 'static,
+    {
+        let (remote, remote_handle) = async { (task)() }.remote_handle();
+        self.spawn(move || futures::executor::block_on(remote));
+        remote_handle
+    }
+
+    /// Fires on the timer thread, which hands every tick to a thread of its own
+    fn schedule_repeating<F>(&self, task: F, interval: Duration) -> AbortHandle
+    where
+        F: Fn() + Send + 'static,
+    {
+        let scheduler = self.clone();
+        let task = Arc::new(Mutex::new(task));
+        timer::schedule_timer(Instant::now() + interval, Some(interval), move || {
+            let task = task.clone();
+            scheduler.spawn(move || (task.lock().unwrap())());
+        })
+    }
+
+    /// Waits on the timer thread, so the task only gets a thread of its own once due
+    fn schedule_after<F>(&self, delay: Duration, task: F) -> AbortHandle
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        let scheduler = self.clone();
+        let mut task = Some(task);
+        timer::schedule_timer(Instant::now() + delay, None, move || {
+            if let Some(task) = task.take() {
+                scheduler.spawn(task);
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::create::create;
+    use crate::new_thread_scheduler::NewThreadScheduler;
+    use crate::observable::Observable;
+    use crate::observer::Observer;
+    use crate::scheduler::Scheduler;
+    use crate::subscriber::Subscriber;
+    use futures::executor::ThreadPool;
+    use std::sync::atomic::{AtomicI32, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn it_runs_producers_on_named_threads() {
+        let collector = Arc::new(Mutex::new(vec![]));
+        let collector_c = collector.clone();
+        let handle = create(|sender: Subscriber<String, String>| {
+            let name = thread::current().name().unwrap_or_default().to_string();
+            sender.next(name).unwrap();
+        })
+        .subscribe_on(NewThreadScheduler::named("producer-"))
+        .subscribe(
+            move |v| collector_c.lock().unwrap().push(v),
+            ThreadPool::new().unwrap(),
+        );
+        futures::executor::block_on(handle);
+        assert_eq!(*collector.lock().unwrap(), vec!["producer-0".to_string()]);
+    }
+
+    #[test]
+    fn it_repeats_tasks_until_aborted() {
+        let scheduler = NewThreadScheduler::new();
+        let counter = Arc::new(AtomicI32::new(0));
+        let counter_c = counter.clone();
+        let handle = scheduler.schedule_repeating(
+            move || {
+                counter_c.fetch_add(1, Ordering::Relaxed);
+            },
+            Duration::from_millis(5),
+        );
+        while counter.load(Ordering::Relaxed) < 3 {
+            thread::sleep(Duration::from_millis(1));
+        }
+        handle.abort();
+        thread::sleep(Duration::from_millis(20));
+        let count = counter.load(Ordering::Relaxed);
+        thread::sleep(Duration::from_millis(20));
+        assert_eq!(counter.load(Ordering::Relaxed), count);
+    }
+}
@@ -0,0 +1,6 @@
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Notification<Item, E> {
+    Next(Item),
+    Error(E),
+    Complete,
+}
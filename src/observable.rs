@@ -1,26 +1,39 @@
 #[cfg(feature = "math")]
 use crate::average::AverageObservable;
-use crate::filter::FilterOp;
+use crate::blocking_iter::BlockingIter;
+use crate::bounded::BoundedObservable;
+use crate::boxed::BoxedObservable;
+use crate::collect;
+use crate::filter::{FilterInlineOp, FilterOp};
 use crate::flatten::FlattenObservable;
 use crate::group_by::{GroupByOp, SenderMap};
-use crate::map::MapOp;
+use crate::into_stream::ObservableStream;
+use crate::map::{MapInlineOp, MapOp};
+use crate::map_err::MapErrOp;
 use crate::merge::MergeObservable;
+use crate::notification::Notification;
+use crate::observe_on::ObserveOnObservable;
+use crate::ordered::{FilterOrderedOp, MapOrderedOp};
+use crate::publish::{ConnectableObservable, RefCountObservable};
 use crate::reduce::ReduceOp;
 use crate::scheduler::Scheduler;
-#[cfg(feature = "recurring")]
 use crate::sliding_window::SlidingWindowObservable;
 use crate::subscribe_on::SubscribeOnObservable;
-use futures::future::RemoteHandle;
-use log::trace;
+use crate::subscriber::Subscriber;
+use crate::subscription::{Subscription, TrySubscription};
+use crate::utils::{self, Subscribed};
+use log::error;
 use num_traits::Zero;
 use std::collections::HashMap;
-use std::io;
-use std::sync::mpsc::Sender;
-use std::sync::{mpsc, Arc, Mutex};
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::ops::ControlFlow;
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
 pub trait Observable: Sized {
     type Item;
+    type Error;
 
     fn map<F, B>(self, f: F) -> MapOp<Self, F>
     where
@@ -32,6 +45,16 @@ pub trait Observable: Sized {
         }
     }
 
+    fn map_err<F, E>(self, f: F) -> MapErrOp<Self, F>
+    where
+        F: Fn(Self::Error) -> E,
+    {
+        MapErrOp {
+            source: self,
+            func: f,
+        }
+    }
+
     fn filter<F>(self, f: F) -> FilterOp<Self, F>
     where
         F: Fn(&Self::Item) -> bool,
@@ -42,6 +65,54 @@ pub trait Observable: Sized {
         }
     }
 
+    /// Like `map`, but applies the function on the operator's own loop instead of scheduling a task per value.
+    /// Cheaper for inexpensive functions, and keeps the order of the values.
+    fn map_inline<F, B>(self, f: F) -> MapInlineOp<Self, F>
+    where
+        F: Fn(Self::Item) -> B,
+    {
+        MapInlineOp {
+            source: self,
+            func: f,
+        }
+    }
+
+    /// Like `filter`, but applies the predicate on the operator's own loop, see `map_inline`.
+    fn filter_inline<F>(self, f: F) -> FilterInlineOp<Self, F>
+    where
+        F: Fn(&Self::Item) -> bool,
+    {
+        FilterInlineOp {
+            source: self,
+            func: f,
+        }
+    }
+
+    /// Like `map`, but emits the results in the order of the incoming values.
+    /// At most `buffer_size` values are processed or wait for their predecessors at any time.
+    fn map_ordered<F, B>(self, f: F, buffer_size: usize) -> MapOrderedOp<Self, F>
+    where
+        F: Fn(Self::Item) -> B,
+    {
+        MapOrderedOp {
+            source: self,
+            func: f,
+            buffer_size,
+        }
+    }
+
+    /// Like `filter`, but keeps the order of the incoming values, see `map_ordered`.
+    fn filter_ordered<F>(self, f: F, buffer_size: usize) -> FilterOrderedOp<Self, F>
+    where
+        F: Fn(&Self::Item) -> bool,
+    {
+        FilterOrderedOp {
+            source: self,
+            func: f,
+            buffer_size,
+        }
+    }
+
     fn reduce<C, R>(self, collector: C, f: R) -> ReduceOp<Self, C, R>
     where
         R: Fn(C, Self::Item) -> C,
@@ -56,7 +127,7 @@ pub trait Observable: Sized {
     fn group_by<GF, Key>(
         self,
         grouping_function: GF,
-    ) -> GroupByOp<Self, GF, SenderMap<Key, Self::Item>>
+    ) -> GroupByOp<Self, GF, SenderMap<Key, Self::Item, Self::Error>>
     where
         GF: Fn(&Self::Item) -> Key,
     {
@@ -74,7 +145,7 @@ pub trait Observable: Sized {
     fn flat_map<F, B, Item>(self, f: F) -> FlattenObservable<MapOp<Self, F>>
     where
         F: Fn(Self::Item) -> B,
-        B: Observable<Item = Item>,
+        B: Observable<Item = Item, Error = Self::Error>,
     {
         FlattenObservable {
             source: MapOp {
@@ -96,7 +167,6 @@ pub trait Observable: Sized {
         }
     }
 
-    #[cfg(feature = "recurring")]
     fn sliding_window<F>(
         self,
         interval: Duration,
@@ -111,17 +181,31 @@ pub trait Observable: Sized {
             interval,
             window_size,
             time_function,
-            buffer: Arc::new(Mutex::new(vec![])),
+            buffer: Arc::new(Mutex::new(Some(vec![]))),
         }
     }
 
-    fn merge(self, source2: Self) -> MergeObservable<Self, Self> {
+    fn merge<S2>(self, source2: S2) -> MergeObservable<Self, S2>
+    where
+        S2: Observable<Item = Self::Item, Error = Self::Error>,
+    {
         MergeObservable {
             source1: self,
             source2,
         }
     }
 
+    /// Bounds the channels of all operators above this one to `capacity` notifications,
+    /// so that producers block while those operators are busy instead of buffering without limit.
+    /// Operators spawning a task per item also keep at most `capacity` of them in flight.
+    /// As the last operator, it bounds the channel to the subscriber as well, so a slow subscriber holds back the producer.
+    fn bounded(self, capacity: usize) -> BoundedObservable<Self> {
+        BoundedObservable {
+            source: self,
+            capacity,
+        }
+    }
+
     fn subscribe_on<Pool>(self, pool: Pool) -> SubscribeOnObservable<Self, Pool>
     where
         Pool: Scheduler + Clone + Send + 'static,
@@ -129,40 +213,294 @@ pub trait Observable: Sized {
         SubscribeOnObservable { source: self, pool }
     }
 
-    fn subscribe<F, S>(self, mut f: F, scheduler: S) -> RemoteHandle<()>
+    /// Runs the operators below, and the subscriber, on `pool` instead of the scheduler passed to `subscribe`.
+    /// The operators above run on the scheduler passed to `subscribe`, or to the next `subscribe_on` above.
+    /// Where several `observe_on`s meet in a `merge`, the operators below it run on the pool of the first one subscribed.
+    /// Only the operator itself moves if `actual_subscribe` was called directly, rather than through `subscribe` or a collector.
+    fn observe_on<Pool>(self, pool: Pool) -> ObserveOnObservable<Self, Pool>
+    where
+        Pool: Scheduler + Clone + Send + 'static,
+    {
+        ObserveOnObservable { source: self, pool }
+    }
+
+    /// Shares one subscription to this pipeline between all subscribers of the returned observable,
+    /// starting it once `connect` is called
+    fn publish(self) -> ConnectableObservable<Self>
+    where
+        Self::Item: Clone + Send + 'static,
+        Self::Error: Clone + Send + 'static,
+    {
+        ConnectableObservable::new(self)
+    }
+
+    /// Like `publish`, but connects with the first subscriber and disconnects once all of them are gone.
+    /// It never reconnects, as the source can only be subscribed to once, see `RefCountObservable`.
+    fn share(self) -> RefCountObservable<Self>
+    where
+        Self::Item: Clone + Send + 'static,
+        Self::Error: Clone + Send + 'static,
+    {
+        self.publish().ref_count()
+    }
+
+    /// Hides the type of this pipeline, see `BoxedObservable`
+    fn boxed(self) -> BoxedObservable<Self::Item, Self::Error>
+    where
+        Self: Send + 'static,
+    {
+        BoxedObservable::new(self)
+    }
+
+    fn subscribe<F, S>(self, f: F, scheduler: S) -> Subscription
     where
         F: FnMut(Self::Item) + Send + 'static,
         S: Scheduler + Clone + Send + 'static,
         Self::Item: Send + 'static,
+        Self::Error: Debug + Send + 'static,
+    {
+        self.subscribe_with(
+            f,
+            |e| error!("Subscribe, unhandled error: {:?}", e),
+            || {},
+            scheduler,
+        )
+    }
+
+    fn subscribe_with<N, EF, C, S>(
+        self,
+        mut on_next: N,
+        on_error: EF,
+        on_complete: C,
+        scheduler: S,
+    ) -> Subscription
+    where
+        N: FnMut(Self::Item) + Send + 'static,
+        EF: FnOnce(Self::Error) + Send + 'static,
+        C: FnOnce() + Send + 'static,
+        S: Scheduler + Clone + Send + 'static,
+        Self::Item: Send + 'static,
+        Self::Error: Send + 'static,
     {
-        let (incoming_tx, incoming_rx) = mpsc::channel();
-        let scheduler_c = scheduler.clone();
-        self.actual_subscribe(incoming_tx, scheduler);
-        scheduler_c.schedule(move || {
-            loop {
-                let message = incoming_rx.recv();
-                match message {
-                    Ok(Ok(message)) => (f)(message),
-                    Ok(Err(e)) => panic!("{}", e.to_string()),
-                    Err(_) => break, // Channel closed
+        let Subscribed {
+            incoming,
+            state,
+            scheduler,
+        } = utils::subscribe_channel(self, scheduler);
+        let state_c = state.clone();
+        let mut on_error = Some(on_error);
+        let mut on_complete = Some(on_complete);
+        let handle = utils::receive(&scheduler, incoming, "Subscribe", move |message| {
+            if state_c.is_closed() {
+                return ControlFlow::Break(()); // Unsubscribed
+            }
+            match message {
+                Ok(Notification::Next(message)) => {
+                    (on_next)(message);
+                    return ControlFlow::Continue(());
                 }
+                Ok(Notification::Error(e)) => (on_error.take().unwrap())(e),
+                Ok(Notification::Complete) => (on_complete.take().unwrap())(),
+                Err(_) => error!("Subscribe, upstream terminated without completing"),
             }
-            trace!("Subscribe finished");
-        })
+            state_c.unsubscribe();
+            ControlFlow::Break(())
+        });
+        Subscription::new(handle, state)
     }
 
-    fn actual_subscribe<O>(self, channel: Sender<io::Result<Self::Item>>, pool: O)
+    /// Subscribes to the pipeline and returns its values as iterator, see `BlockingIter`
+    fn into_blocking_iter<S>(self, scheduler: S) -> BlockingIter<Self::Item, Self::Error>
+    where
+        S: Scheduler + Clone + Send + 'static,
+        Self::Item: Send + 'static,
+        Self::Error: Send + 'static,
+    {
+        BlockingIter::new(self, scheduler)
+    }
+
+    /// Subscribes to the pipeline and returns its values as `futures::Stream`, see `ObservableStream`
+    fn into_stream<S>(self, scheduler: S) -> ObservableStream<Self::Item, Self::Error>
+    where
+        S: Scheduler + Clone + Send + 'static,
+        Self::Item: Send + 'static,
+        Self::Error: Send + 'static,
+    {
+        ObservableStream::new(self, scheduler)
+    }
+
+    /// Like `subscribe`, but the subscription resolves to why the stream ended without completing, if it did
+    fn try_subscribe<F, S>(self, mut f: F, scheduler: S) -> TrySubscription<(), Self::Error>
+    where
+        F: FnMut(Self::Item) + Send + 'static,
+        S: Scheduler + Clone + Send + 'static,
+        Self::Item: Send + 'static,
+        Self::Error: Send + 'static,
+    {
+        collect::collect(
+            self,
+            (),
+            move |_, value| {
+                (f)(value);
+                ControlFlow::Continue(())
+            },
+            |()| (),
+            scheduler,
+        )
+    }
+
+    /// Collects all values, resolving once the stream completed
+    fn to_vec<S>(self, scheduler: S) -> TrySubscription<Vec<Self::Item>, Self::Error>
+    where
+        S: Scheduler + Clone + Send + 'static,
+        Self::Item: Send + 'static,
+        Self::Error: Send + 'static,
+    {
+        collect::collect(
+            self,
+            vec![],
+            |values, value| {
+                values.push(value);
+                ControlFlow::Continue(())
+            },
+            |values| values,
+            scheduler,
+        )
+    }
+
+    /// Collects the values by the key `key_fn` returns for them, later values replacing earlier ones
+    fn to_hash_map<K, KF, S>(
+        self,
+        key_fn: KF,
+        scheduler: S,
+    ) -> TrySubscription<HashMap<K, Self::Item>, Self::Error>
+    where
+        K: Eq + Hash + Send + 'static,
+        KF: Fn(&Self::Item) -> K + Send + 'static,
+        S: Scheduler + Clone + Send + 'static,
+        Self::Item: Send + 'static,
+        Self::Error: Send + 'static,
+    {
+        collect::collect(
+            self,
+            HashMap::new(),
+            move |map, value| {
+                map.insert(key_fn(&value), value);
+                ControlFlow::Continue(())
+            },
+            |map| map,
+            scheduler,
+        )
+    }
+
+    /// Collects the values by the key `key_fn` returns for them, keeping all values per key in order
+    fn to_multimap<K, KF, S>(
+        self,
+        key_fn: KF,
+        scheduler: S,
+    ) -> TrySubscription<HashMap<K, Vec<Self::Item>>, Self::Error>
+    where
+        K: Eq + Hash + Send + 'static,
+        KF: Fn(&Self::Item) -> K + Send + 'static,
+        S: Scheduler + Clone + Send + 'static,
+        Self::Item: Send + 'static,
+        Self::Error: Send + 'static,
+    {
+        collect::collect(
+            self,
+            HashMap::new(),
+            move |map: &mut HashMap<K, Vec<Self::Item>>, value| {
+                map.entry(key_fn(&value)).or_default().push(value);
+                ControlFlow::Continue(())
+            },
+            |map| map,
+            scheduler,
+        )
+    }
+
+    /// Resolves to the first value, unsubscribing right after it, or `None` if the stream completed empty
+    fn first<S>(self, scheduler: S) -> TrySubscription<Option<Self::Item>, Self::Error>
+    where
+        S: Scheduler + Clone + Send + 'static,
+        Self::Item: Send + 'static,
+        Self::Error: Send + 'static,
+    {
+        collect::collect(
+            self,
+            None,
+            |first, value| {
+                *first = Some(value);
+                ControlFlow::Break(())
+            },
+            |first| first,
+            scheduler,
+        )
+    }
+
+    /// Resolves to the last value once the stream completed, or `None` if it was empty
+    fn last<S>(self, scheduler: S) -> TrySubscription<Option<Self::Item>, Self::Error>
+    where
+        S: Scheduler + Clone + Send + 'static,
+        Self::Item: Send + 'static,
+        Self::Error: Send + 'static,
+    {
+        collect::collect(
+            self,
+            None,
+            |last, value| {
+                *last = Some(value);
+                ControlFlow::Continue(())
+            },
+            |last| last,
+            scheduler,
+        )
+    }
+
+    /// Resolves to the number of values once the stream completed
+    fn count<S>(self, scheduler: S) -> TrySubscription<usize, Self::Error>
+    where
+        S: Scheduler + Clone + Send + 'static,
+        Self::Item: Send + 'static,
+        Self::Error: Send + 'static,
+    {
+        collect::collect(
+            self,
+            0,
+            |count, _| {
+                *count += 1;
+                ControlFlow::Continue(())
+            },
+            |count| count,
+            scheduler,
+        )
+    }
+
+    /// Subscribes this operator, and the ones above it, sending into `channel` and running on `pool`.
+    /// `subscribe` and the collectors hand the pipeline a scheduler which only starts running what was scheduled
+    /// on it once the whole pipeline is subscribed, and which `observe_on` recognises by its type to move the operators below it.
+    /// Operators therefore have to pass `pool` on to their sources as it is, rather than a scheduler of their own.
+    fn actual_subscribe<O>(self, channel: Subscriber<Self::Item, Self::Error>, pool: O)
     where
         O: Scheduler + Clone + Send + 'static;
+
+    /// The capacity of the channel to the subscriber, only set if `bounded` is the last operator
+    fn capacity(&self) -> Option<usize> {
+        None
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::create::create;
     use crate::from_iter::from_iter;
     use crate::observable::Observable;
+    use crate::observer::Observer;
+    use crate::subscriber::Subscriber;
+    use crate::subscription::Terminated;
     use futures::executor::ThreadPool;
-    use std::sync::atomic::{AtomicI32, Ordering};
-    use std::sync::Arc;
+    use std::sync::atomic::{AtomicBool, AtomicI32, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
 
     #[test]
     fn it_can_mut_access_external_state() {
@@ -179,4 +517,88 @@ mod tests {
         futures::executor::block_on(handle);
         assert_eq!(collector_c.load(Ordering::Relaxed), 45);
     }
+
+    #[test]
+    fn it_calls_the_error_handler() {
+        let events = Arc::new(Mutex::new(vec![]));
+        let (events_next, events_error, events_complete) =
+            (events.clone(), events.clone(), events.clone());
+        let handle = create(|sender: Subscriber<i32, String>| {
+            sender.next(1).unwrap();
+            sender.error("failed".to_string()).unwrap();
+        })
+        .subscribe_with(
+            move |v| events_next.lock().unwrap().push(v.to_string()),
+            move |e| events_error.lock().unwrap().push(e),
+            move || events_complete.lock().unwrap().push("complete".to_string()),
+            ThreadPool::new().unwrap(),
+        );
+        futures::executor::block_on(handle);
+        assert_eq!(*events.lock().unwrap(), vec!["1", "failed"]);
+    }
+
+    #[test]
+    fn it_calls_the_complete_handler() {
+        let collector = Arc::new(AtomicI32::new(0));
+        let (collector_next, collector_complete) = (collector.clone(), collector.clone());
+        let handle = from_iter(0..10).subscribe_with(
+            move |v| {
+                collector_next.fetch_add(v, Ordering::Relaxed);
+            },
+            |_| unreachable!(),
+            move || {
+                collector_complete.fetch_add(100, Ordering::Relaxed);
+            },
+            ThreadPool::new().unwrap(),
+        );
+        futures::executor::block_on(handle);
+        assert_eq!(collector.load(Ordering::Relaxed), 145);
+    }
+
+    #[test]
+    fn it_resolves_to_the_error() {
+        let handle = create(|sender: Subscriber<i32, String>| {
+            sender.error("failed".to_string()).unwrap();
+        })
+        .map(|v| v + 1)
+        .try_subscribe(|_| {}, ThreadPool::new().unwrap());
+        assert_eq!(
+            futures::executor::block_on(handle),
+            Err(Terminated::Error("failed".to_string()))
+        );
+    }
+
+    #[test]
+    fn it_resolves_to_dropped_if_upstream_panicked() {
+        let handle = create(|sender: Subscriber<i32, String>| {
+            sender.next(1).unwrap();
+            panic!("Producer failed");
+        })
+        .try_subscribe(|_| {}, ThreadPool::new().unwrap());
+        assert_eq!(
+            futures::executor::block_on(handle),
+            Err(Terminated::Dropped)
+        );
+    }
+
+    #[test]
+    fn it_unsubscribes() {
+        let producer_stopped = Arc::new(AtomicBool::new(false));
+        let producer_stopped_c = producer_stopped.clone();
+        let subscription = create(move |sender: Subscriber<i32, String>| {
+            while !sender.is_closed() {
+                let _ = sender.next(1);
+                std::thread::sleep(Duration::from_millis(1));
+            }
+            producer_stopped_c.store(true, Ordering::Relaxed);
+        })
+        .map(|v| v * 2)
+        .subscribe(|_| {}, ThreadPool::new().unwrap());
+        std::thread::sleep(Duration::from_millis(10));
+        subscription.unsubscribe();
+        assert!(subscription.is_closed());
+        futures::executor::block_on(subscription);
+        std::thread::sleep(Duration::from_millis(10));
+        assert!(producer_stopped.load(Ordering::Relaxed));
+    }
 }
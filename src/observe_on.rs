@@ -0,0 +1,152 @@
+use crate::deferred_scheduler::DeferredScheduler;
+use crate::observable::Observable;
+use crate::scheduler::Scheduler;
+use crate::subscriber::Subscriber;
+use crate::utils;
+
+pub struct ObserveOnObservable<Source, Pool> {
+    pub(crate) source: Source,
+    pub(crate) pool: Pool,
+}
+
+impl<Source, Pool> Observable for ObserveOnObservable<Source, Pool>
+where
+    Source: Observable,
+    Source::Item: Send + 'static,
+    Source::Error: Send + 'static,
+    Pool: Scheduler + Clone + Send + 'static,
+{
+    type Item = Source::Item;
+    type Error = Source::Error;
+
+    fn actual_subscribe<O>(self, channel: Subscriber<Self::Item, Self::Error>, pool: O)
+    where
+        O: Scheduler + Clone + Send + 'static,
+    {
+        let (incoming_tx, incoming_rx) = channel.upstream::<Source::Item, Source::Error>();
+        utils::forward_messages(incoming_rx, channel, self.pool.clone());
+        match DeferredScheduler::downcast(&pool) {
+            // Everything below was scheduled on `pool` already, move it to this operator's pool
+            Some(deferred) => {
+                let upstream = deferred.upstream();
+                deferred.observe_on(self.pool);
+                self.source.actual_subscribe(incoming_tx, upstream);
+            }
+            // Subscribed directly with another scheduler, only this operator's loop moves
+            None => self.source.actual_subscribe(incoming_tx, pool),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::create::create;
+    use crate::from_iter::from_iter;
+    use crate::observable::Observable;
+    use crate::observer::Observer;
+    use crate::subscriber::Subscriber;
+    use futures::executor::{block_on, ThreadPool};
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+
+    fn thread_name() -> String {
+        thread::current().name().unwrap_or_default().to_string()
+    }
+
+    #[test]
+    fn it_observes_on_another_pool() {
+        let cpu = ThreadPool::builder()
+            .name_prefix("cpu-")
+            .pool_size(4)
+            .create()
+            .unwrap();
+        let io = ThreadPool::builder()
+            .name_prefix("io-")
+            .pool_size(4)
+            .create()
+            .unwrap();
+        let collector = Arc::new(Mutex::new(vec![]));
+        let collector_c = collector.clone();
+        let handle = create(|sender: Subscriber<i32, String>| {
+            for i in 0..10 {
+                sender.next(i).unwrap();
+            }
+        })
+        .map(|v| (v, thread_name()))
+        .observe_on(io)
+        .map_inline(|(v, mapped_on)| (v, mapped_on, thread_name()))
+        .subscribe(
+            move |(v, mapped_on, observed_on)| {
+                collector_c
+                    .lock()
+                    .unwrap()
+                    .push((v, mapped_on, observed_on, thread_name()))
+            },
+            cpu,
+        );
+        futures::executor::block_on(handle);
+        let collector = collector.lock().unwrap();
+        assert_eq!(collector.len(), 10);
+        for (_, mapped_on, observed_on, received_on) in collector.iter() {
+            assert!(mapped_on.starts_with("cpu-"), "{}", mapped_on);
+            assert!(observed_on.starts_with("io-"), "{}", observed_on);
+            assert!(received_on.starts_with("io-"), "{}", received_on);
+        }
+    }
+
+    fn named_pool(name_prefix: &str) -> ThreadPool {
+        ThreadPool::builder()
+            .name_prefix(name_prefix)
+            .pool_size(4)
+            .create()
+            .unwrap()
+    }
+
+    #[test]
+    fn it_observes_on_inside_merge() {
+        let merged = from_iter(0..3)
+            .observe_on(named_pool("first-"))
+            .merge(from_iter(10..13).observe_on(named_pool("second-")))
+            .to_vec(ThreadPool::new().unwrap());
+        let mut values = block_on(merged).unwrap();
+        values.sort();
+        assert_eq!(values, vec![0, 1, 2, 10, 11, 12]);
+    }
+
+    #[test]
+    fn it_observes_on_inside_flat_map() {
+        let io = named_pool("io-");
+        let flattened = from_iter(0..3)
+            .flat_map(move |v| {
+                from_iter(v * 10..v * 10 + 2)
+                    .map_inline(|v| (v, thread_name()))
+                    .observe_on(io.clone())
+            })
+            .to_vec(ThreadPool::new().unwrap());
+        let mut values = block_on(flattened).unwrap();
+        values.sort();
+        assert_eq!(
+            values.iter().map(|(v, _)| *v).collect::<Vec<_>>(),
+            vec![0, 1, 10, 11, 20, 21]
+        );
+        for (_, mapped_on) in values {
+            assert!(!mapped_on.starts_with("io-"), "{}", mapped_on);
+        }
+    }
+
+    #[test]
+    fn it_observes_on_above_subscribe_on() {
+        let values = create(|sender: Subscriber<String, String>| {
+            sender.next(thread_name()).unwrap();
+        })
+        .observe_on(named_pool("io-"))
+        .map_inline(|produced_on| (produced_on, thread_name()))
+        .subscribe_on(named_pool("producer-"))
+        .map_inline(|(produced_on, observed_on)| (produced_on, observed_on, thread_name()))
+        .to_vec(ThreadPool::new().unwrap());
+        let (produced_on, observed_on, received_on) = block_on(values).unwrap().remove(0);
+        assert!(produced_on.starts_with("producer-"), "{}", produced_on);
+        assert!(observed_on.starts_with("io-"), "{}", observed_on);
+        assert!(received_on.starts_with("io-"), "{}", received_on);
+    }
+}
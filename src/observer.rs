@@ -1,23 +1,54 @@
-use std::io::{Error, ErrorKind};
-use std::sync::mpsc::{SendError, Sender};
+use crate::notification::Notification;
+use std::sync::mpsc::{SendError, Sender, SyncSender};
+
+pub type ObserverResult<Item, E> = Result<(), SendError<Notification<Item, E>>>;
 
 pub trait Observer {
     type Item;
-    fn next(&self, value: Self::Item) -> Result<(), SendError<std::io::Result<Self::Item>>>;
-    fn error(&self, err: Error) -> Result<(), SendError<std::io::Result<Self::Item>>>;
+    type Error;
+    fn next(&self, value: Self::Item) -> ObserverResult<Self::Item, Self::Error>;
+    fn error(&self, err: Self::Error) -> ObserverResult<Self::Item, Self::Error>;
+    fn complete(&self) -> ObserverResult<Self::Item, Self::Error>;
 }
 
-impl<Item> Observer for Sender<std::io::Result<Item>>
+impl<Item, E> Observer for Sender<Notification<Item, E>>
 where
     Item: Send + 'static,
+    E: Send + 'static,
 {
     type Item = Item;
+    type Error = E;
+
+    fn next(&self, value: Self::Item) -> ObserverResult<Item, E> {
+        self.send(Notification::Next(value))
+    }
+
+    fn error(&self, err: Self::Error) -> ObserverResult<Item, E> {
+        self.send(Notification::Error(err))
+    }
+
+    fn complete(&self) -> ObserverResult<Item, E> {
+        self.send(Notification::Complete)
+    }
+}
+
+impl<Item, E> Observer for SyncSender<Notification<Item, E>>
+where
+    Item: Send + 'static,
+    E: Send + 'static,
+{
+    type Item = Item;
+    type Error = E;
+
+    fn next(&self, value: Self::Item) -> ObserverResult<Item, E> {
+        self.send(Notification::Next(value))
+    }
 
-    fn next(&self, value: Self::Item) -> Result<(), SendError<std::io::Result<Item>>> {
-        self.send(Ok(value))
+    fn error(&self, err: Self::Error) -> ObserverResult<Item, E> {
+        self.send(Notification::Error(err))
     }
 
-    fn error(&self, err: Error) -> Result<(), SendError<std::io::Result<Item>>> {
-        self.send(Err(Error::new(ErrorKind::Other, err)))
+    fn complete(&self) -> ObserverResult<Item, E> {
+        self.send(Notification::Complete)
     }
 }
@@ -0,0 +1,269 @@
+use crate::notification::Notification;
+use crate::observable::Observable;
+use crate::scheduler::Scheduler;
+use crate::subscriber::Subscriber;
+use crate::utils;
+use crate::utils::{Permit, Permits};
+
+use std::collections::BTreeMap;
+use std::ops::ControlFlow;
+use std::sync::mpsc::{self, Receiver};
+use std::sync::Arc;
+
+pub struct MapOrderedOp<S, M> {
+    pub(crate) source: S,
+    pub(crate) func: M,
+    pub(crate) buffer_size: usize,
+}
+
+pub struct FilterOrderedOp<S, F> {
+    pub(crate) source: S,
+    pub(crate) func: F,
+    pub(crate) buffer_size: usize,
+}
+
+impl<Item, S, M> Observable for MapOrderedOp<S, M>
+where
+    S: Observable,
+    S::Item: Send + 'static,
+    S::Error: Send + 'static,
+    M: Fn(S::Item) -> Item + Clone + Send + 'static,
+    Item: Send + 'static,
+{
+    type Item = Item;
+    type Error = S::Error;
+
+    fn actual_subscribe<O>(self, channel: Subscriber<Self::Item, Self::Error>, pool: O)
+    where
+        O: Scheduler + Clone + Send + 'static,
+    {
+        let func = self.func;
+        subscribe_ordered(
+            self.source,
+            move |value| Some((func)(value)),
+            self.buffer_size,
+            channel,
+            pool,
+        );
+    }
+}
+
+impl<S, F> Observable for FilterOrderedOp<S, F>
+where
+    S: Observable,
+    S::Item: Send + 'static,
+    S::Error: Send + 'static,
+    F: Fn(&S::Item) -> bool + Clone + Send + 'static,
+{
+    type Item = S::Item;
+    type Error = S::Error;
+
+    fn actual_subscribe<O>(self, channel: Subscriber<Self::Item, Self::Error>, pool: O)
+    where
+        O: Scheduler + Clone + Send + 'static,
+    {
+        let func = self.func;
+        subscribe_ordered(
+            self.source,
+            move |value| (func)(&value).then_some(value),
+            self.buffer_size,
+            channel,
+            pool,
+        );
+    }
+}
+
+/// The terminating notifications carry the number of values before them, as those have to be emitted first
+enum Resequence<Item, E> {
+    Value(u64, Option<Item>, Option<Permit>),
+    Error(u64, E),
+    Complete(u64),
+}
+
+/// Evaluates `func` for each value in a task of its own, and re-sequences the results in upstream order.
+/// At most `buffer_size` values are in flight or waiting for their predecessors at any time.
+fn subscribe_ordered<S, F, Item, O>(
+    source: S,
+    func: F,
+    buffer_size: usize,
+    channel: Subscriber<Item, S::Error>,
+    pool: O,
+) where
+    S: Observable,
+    S::Item: Send + 'static,
+    S::Error: Send + 'static,
+    F: Fn(S::Item) -> Option<Item> + Clone + Send + 'static,
+    Item: Send + 'static,
+    O: Scheduler + Clone + Send + 'static,
+{
+    let (incoming_tx, incoming_rx) = channel.upstream::<S::Item, S::Error>();
+    let (results_tx, results_rx) = mpsc::channel();
+    let permits = Arc::new(Permits::new(buffer_size));
+    resequence(results_rx, channel.clone(), pool.clone());
+    let pool_c = pool.clone();
+    let mut sequence = 0;
+    utils::receive_with_permits(
+        &pool,
+        incoming_rx,
+        Some(permits),
+        "Ordered",
+        move |message, permit| match message {
+            Ok(Notification::Next(_)) if channel.is_closed() => ControlFlow::Break(()),
+            Ok(Notification::Next(message)) => {
+                let func_c = func.clone();
+                let results_tx_c = results_tx.clone();
+                let index = sequence;
+                pool_c.schedule_detached(move || {
+                    let out = (func_c)(message);
+                    let _ = results_tx_c.send(Resequence::Value(index, out, permit));
+                });
+                sequence += 1;
+                ControlFlow::Continue(())
+            }
+            Ok(Notification::Error(e)) => {
+                let _ = results_tx.send(Resequence::Error(sequence, e));
+                ControlFlow::Break(())
+            }
+            Ok(Notification::Complete) => {
+                let _ = results_tx.send(Resequence::Complete(sequence));
+                ControlFlow::Break(())
+            }
+            Err(_) => ControlFlow::Break(()), // Upstream terminated without completing
+        },
+    )
+    .forget();
+    source.actual_subscribe(incoming_tx, pool);
+}
+
+fn resequence<Item, E, O>(
+    results: Receiver<Resequence<Item, E>>,
+    channel: Subscriber<Item, E>,
+    pool: O,
+) where
+    Item: Send + 'static,
+    E: Send + 'static,
+    O: Scheduler,
+{
+    let mut pending = BTreeMap::new();
+    let mut next = 0;
+    let mut terminal = None;
+    utils::receive(&pool, results, "Resequencing", move |message| {
+        match message {
+            Ok(Resequence::Value(index, out, permit)) => {
+                pending.insert(index, (out, permit));
+            }
+            Ok(Resequence::Error(count, e)) => terminal = Some((count, Notification::Error(e))),
+            Ok(Resequence::Complete(count)) => terminal = Some((count, Notification::Complete)),
+            Err(_) => return ControlFlow::Break(()), // Upstream terminated without completing
+        }
+        while let Some((out, _permit)) = pending.remove(&next) {
+            next += 1;
+            if let Some(out) = out {
+                if channel.send(Notification::Next(out)).is_err() {
+                    return ControlFlow::Break(());
+                }
+            }
+        }
+        if let Some((count, _)) = &terminal {
+            if *count == next {
+                let (_, notification) = terminal.take().unwrap();
+                let _ = channel.send(notification);
+                return ControlFlow::Break(());
+            }
+        }
+        ControlFlow::Continue(())
+    })
+    .forget();
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::create::create;
+    use crate::observable::Observable;
+    use crate::observer::Observer;
+    use crate::subscriber::Subscriber;
+    use futures::executor::ThreadPool;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    #[test]
+    fn it_maps_in_order() {
+        let collector = Arc::new(Mutex::new(vec![]));
+        let collector_c = collector.clone();
+        let handle = create(|sender: Subscriber<u64, String>| {
+            for i in 0..20 {
+                sender.next(i).unwrap();
+            }
+        })
+        .map_ordered(
+            |v| {
+                std::thread::sleep(Duration::from_millis((20 - v) % 7));
+                v * 2
+            },
+            4,
+        )
+        .subscribe(
+            move |v| collector.lock().unwrap().push(v),
+            ThreadPool::new().unwrap(),
+        );
+        futures::executor::block_on(handle);
+        assert_eq!(
+            *collector_c.lock().unwrap(),
+            (0..20).map(|v| v * 2).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn it_filters_in_order() {
+        let collector = Arc::new(Mutex::new(vec![]));
+        let collector_c = collector.clone();
+        let handle = create(|sender: Subscriber<u64, String>| {
+            for i in 0..20 {
+                sender.next(i).unwrap();
+            }
+        })
+        .filter_ordered(
+            |v| {
+                std::thread::sleep(Duration::from_millis((20 - v) % 7));
+                v % 3 == 0
+            },
+            4,
+        )
+        .subscribe(
+            move |v| collector.lock().unwrap().push(v),
+            ThreadPool::new().unwrap(),
+        );
+        futures::executor::block_on(handle);
+        assert_eq!(*collector_c.lock().unwrap(), vec![0, 3, 6, 9, 12, 15, 18]);
+    }
+
+    #[test]
+    fn it_emits_errors_after_the_values_before_them() {
+        let events = Arc::new(Mutex::new(vec![]));
+        let (events_next, events_error) = (events.clone(), events.clone());
+        let handle = create(|sender: Subscriber<u64, String>| {
+            for i in 0..5 {
+                sender.next(i).unwrap();
+            }
+            sender.error("failed".to_string()).unwrap();
+        })
+        .map_ordered(
+            |v| {
+                std::thread::sleep(Duration::from_millis(10 - v * 2));
+                v
+            },
+            8,
+        )
+        .subscribe_with(
+            move |v| events_next.lock().unwrap().push(v.to_string()),
+            move |e| events_error.lock().unwrap().push(e),
+            || {},
+            ThreadPool::new().unwrap(),
+        );
+        futures::executor::block_on(handle);
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec!["0", "1", "2", "3", "4", "failed"]
+        );
+    }
+}
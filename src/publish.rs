@@ -0,0 +1,240 @@
+use crate::observable::Observable;
+use crate::observer::Observer;
+use crate::scheduler::Scheduler;
+use crate::subject::PublishSubject;
+use crate::subscriber::Subscriber;
+use crate::subscription::Subscription;
+use std::sync::{Arc, Mutex};
+
+/// Shares a single subscription to its source between all of its subscribers.
+/// The source is only subscribed to on `connect`, so every subscriber subscribed before receives all values.
+pub struct ConnectableObservable<S>
+where
+    S: Observable,
+{
+    subject: PublishSubject<S::Item, S::Error>,
+    source: Arc<Mutex<Option<S>>>,
+}
+
+/// Connects its source once subscribed to, and disconnects it once all subscribers are gone.
+/// The source runs on the scheduler of the subscriber connecting it.
+/// As the source can't be subscribed to twice, subscribers arriving after it was disconnected are dropped
+/// without completing, so collectors and `try_subscribe` resolve to `Terminated::Dropped`.
+pub struct RefCountObservable<S>
+where
+    S: Observable,
+{
+    connectable: ConnectableObservable<S>,
+    ref_count: Arc<Mutex<RefCount>>,
+}
+
+#[derive(Default)]
+struct RefCount {
+    subscribers: usize,
+    connection: Option<Subscription>,
+    disconnected: bool,
+}
+
+impl<S> ConnectableObservable<S>
+where
+    S: Observable,
+    S::Item: Clone + Send + 'static,
+    S::Error: Clone + Send + 'static,
+{
+    pub(crate) fn new(source: S) -> Self {
+        ConnectableObservable {
+            subject: PublishSubject::new(),
+            source: Arc::new(Mutex::new(Some(source))),
+        }
+    }
+
+    /// Subscribes to the source, returning `None` if it was connected before
+    pub fn connect<O>(&self, scheduler: O) -> Option<Subscription>
+    where
+        O: Scheduler + Clone + Send + 'static,
+    {
+        let source = self.source.lock().unwrap().take()?;
+        let (on_next, on_error, on_complete) = (
+            self.subject.clone(),
+            self.subject.clone(),
+            self.subject.clone(),
+        );
+        Some(source.subscribe_with(
+            move |value| {
+                let _ = on_next.next(value);
+            },
+            move |e| {
+                let _ = on_error.error(e);
+            },
+            move || {
+                let _ = on_complete.complete();
+            },
+            scheduler,
+        ))
+    }
+
+    pub fn ref_count(self) -> RefCountObservable<S> {
+        RefCountObservable {
+            connectable: self,
+            ref_count: Arc::default(),
+        }
+    }
+}
+
+impl<S> Clone for ConnectableObservable<S>
+where
+    S: Observable,
+{
+    fn clone(&self) -> Self {
+        ConnectableObservable {
+            subject: self.subject.clone(),
+            source: self.source.clone(),
+        }
+    }
+}
+
+impl<S> Clone for RefCountObservable<S>
+where
+    S: Observable,
+{
+    fn clone(&self) -> Self {
+        RefCountObservable {
+            connectable: self.connectable.clone(),
+            ref_count: self.ref_count.clone(),
+        }
+    }
+}
+
+impl<S> Observable for ConnectableObservable<S>
+where
+    S: Observable,
+    S::Item: Clone + Send + 'static,
+    S::Error: Clone + Send + 'static,
+{
+    type Item = S::Item;
+    type Error = S::Error;
+
+    fn actual_subscribe<O>(self, channel: Subscriber<Self::Item, Self::Error>, pool: O)
+    where
+        O: Scheduler + Clone + Send + 'static,
+    {
+        self.subject.actual_subscribe(channel, pool);
+    }
+}
+
+impl<S> Observable for RefCountObservable<S>
+where
+    S: Observable,
+    S::Item: Clone + Send + 'static,
+    S::Error: Clone + Send + 'static,
+{
+    type Item = S::Item;
+    type Error = S::Error;
+
+    fn actual_subscribe<O>(self, channel: Subscriber<Self::Item, Self::Error>, pool: O)
+    where
+        O: Scheduler + Clone + Send + 'static,
+    {
+        {
+            let mut ref_count = self.ref_count.lock().unwrap();
+            // Subscribers arriving after the source terminated still receive how it did
+            if ref_count.disconnected && !self.connectable.subject.is_terminated() {
+                return;
+            }
+            ref_count.subscribers += 1;
+            self.connectable
+                .subject
+                .clone()
+                .actual_subscribe(channel.clone(), pool.clone());
+            if ref_count.subscribers == 1 {
+                ref_count.connection = self.connectable.connect(pool);
+            }
+        }
+        let ref_count = self.ref_count;
+        channel.add_teardown(move || {
+            let mut ref_count = ref_count.lock().unwrap();
+            ref_count.subscribers -= 1;
+            if ref_count.subscribers == 0 {
+                if let Some(connection) = ref_count.connection.take() {
+                    connection.unsubscribe();
+                    ref_count.disconnected = true;
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::create::create;
+    use crate::observable::Observable;
+    use crate::observer::Observer;
+    use crate::subscriber::Subscriber;
+    use crate::subscription::Terminated;
+    use futures::executor::{block_on, ThreadPool};
+    use std::sync::atomic::{AtomicI32, Ordering};
+    use std::sync::{mpsc, Arc};
+    use std::time::Duration;
+
+    #[test]
+    fn it_connects_published_observables() {
+        let pool = ThreadPool::new().unwrap();
+        let subscriptions = Arc::new(AtomicI32::new(0));
+        let subscriptions_c = subscriptions.clone();
+        let published = create(move |sender: Subscriber<i32, String>| {
+            subscriptions_c.fetch_add(1, Ordering::Relaxed);
+            for i in 0..3 {
+                sender.next(i).unwrap();
+            }
+        })
+        .publish();
+        let first = published.clone().to_vec(pool.clone());
+        let second = published.clone().map_inline(|v| v * 2).to_vec(pool.clone());
+        let connection = published.connect(pool.clone()).unwrap();
+        assert!(published.connect(pool).is_none());
+        assert_eq!(block_on(first), Ok(vec![0, 1, 2]));
+        assert_eq!(block_on(second), Ok(vec![0, 2, 4]));
+        block_on(connection);
+        assert_eq!(subscriptions.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn it_disconnects_once_all_subscribers_left() {
+        let pool = ThreadPool::new().unwrap();
+        let (stopped_tx, stopped_rx) = mpsc::channel();
+        let (subscribed_tx, subscribed_rx) = mpsc::channel::<()>();
+        let shared = create(move |sender: Subscriber<i32, String>| {
+            // Holds the values back until both subscribers are there, the first one connecting right away
+            subscribed_rx.recv().unwrap();
+            let mut i = 0;
+            while sender.next(i).is_ok() {
+                i += 1;
+            }
+            stopped_tx.send(()).unwrap();
+        })
+        .share();
+        let first = shared.clone().first(pool.clone());
+        let second = shared.clone().first(pool.clone());
+        subscribed_tx.send(()).unwrap();
+        assert!(block_on(first).unwrap().is_some());
+        assert!(block_on(second).unwrap().is_some());
+        stopped_rx.recv_timeout(Duration::from_secs(10)).unwrap();
+        assert_eq!(block_on(shared.to_vec(pool)), Err(Terminated::Dropped));
+    }
+
+    #[test]
+    fn it_completes_subscribers_arriving_after_the_source_completed() {
+        let pool = ThreadPool::new().unwrap();
+        let shared = create(|sender: Subscriber<i32, String>| {
+            for i in 0..3 {
+                sender.next(i).unwrap();
+            }
+        })
+        .share();
+        assert_eq!(
+            block_on(shared.clone().to_vec(pool.clone())),
+            Ok(vec![0, 1, 2])
+        );
+        assert_eq!(block_on(shared.to_vec(pool)), Ok(vec![]));
+    }
+}
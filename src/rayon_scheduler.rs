@@ -0,0 +1,122 @@
+use crate::new_thread_scheduler::NewThreadScheduler;
+use crate::scheduler::{LoopStep, Scheduler, Wait};
+use crate::timer;
+use futures::future::{AbortHandle, RemoteHandle};
+use futures::FutureExt;
+use rayon::ThreadPool;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Runs the tasks, e.g. the values `map` and `filter` evaluate, on Rayon's workers.
+/// The operator loops, and producers like `create` which may block on a bounded pipeline, run on
+/// threads of their own, as a worker blocked waiting for a job queued behind it would deadlock the pool.
+/// Implemented for `Arc<ThreadPool>`, as operators need to clone their scheduler.
+impl Scheduler for Arc<ThreadPool> {
+    fn schedule<R>(&self, task: impl FnOnce() -> R + Send + 'static) -> RemoteHandle<R>
+    where
+        R: Send + 'static,
+    {
+        let (remote, remote_handle) = async { (task)() }.remote_handle();
+        self.spawn_fifo(move || {
+            let _ = remote.now_or_never();
+        });
+        remote_handle
+    }
+
+    fn schedule_loop<R>(
+        &self,
+        step: impl FnMut(Wait) -> LoopStep<R> + Send + 'static,
+    ) -> RemoteHandle<R>
+    where
+        R: Send + 'static,
+    {
+        NewThreadScheduler::new().schedule_loop(step)
+    }
+
+    /// Rayon has no timers, so this fires on the timer thread, which hands the task to the pool
+    fn schedule_repeating<F>(&self, task: F, interval: Duration) -> AbortHandle
+    where
+        F: Fn() + Send + 'static,
+    {
+        let pool = self.clone();
+        let task = Arc::new(Mutex::new(task));
+        timer::schedule_timer(Instant::now() + interval, Some(interval), move || {
+            let task = task.clone();
+            pool.spawn_fifo(move || (task.lock().unwrap())());
+        })
+    }
+
+    fn schedule_after<F>(&self, delay: Duration, task: F) -> AbortHandle
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        let pool = self.clone();
+        let mut task = Some(task);
+        timer::schedule_timer(Instant::now() + delay, None, move || {
+            if let Some(task) = task.take() {
+                pool.spawn_fifo(task);
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::create::create;
+    use crate::from_iter::from_iter;
+    use crate::observable::Observable;
+    use crate::observer::Observer;
+    use crate::scheduler::Scheduler;
+    use crate::subscriber::Subscriber;
+    use rayon::ThreadPoolBuilder;
+    use std::convert::Infallible;
+    use std::sync::{mpsc, Arc, Mutex};
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn it_runs_pipelines_on_a_single_worker() {
+        let pool = Arc::new(ThreadPoolBuilder::new().num_threads(1).build().unwrap());
+        let collector = Arc::new(Mutex::new(vec![]));
+        let collector_c = collector.clone();
+        let subscription = from_iter(0..100)
+            .map(|v| v * 2)
+            .filter(|v| v % 3 == 0)
+            .reduce(0, |acc, v| acc + v)
+            .subscribe(move |v| collector_c.lock().unwrap().push(v), pool);
+        futures::executor::block_on(subscription);
+        let expected = (0..100).map(|v| v * 2).filter(|v| v % 3 == 0).sum::<i32>();
+        assert_eq!(*collector.lock().unwrap(), vec![expected]);
+    }
+
+    #[test]
+    fn it_runs_bounded_pipelines_on_a_single_worker() {
+        let pool = Arc::new(ThreadPoolBuilder::new().num_threads(1).build().unwrap());
+        let count = create(|sender: Subscriber<i32, Infallible>| {
+            for i in 0..50 {
+                sender.next(i).unwrap();
+            }
+        })
+        .map(|v| v * 2)
+        .bounded(2)
+        .count(pool);
+        assert_eq!(futures::executor::block_on(count), Ok(50));
+    }
+
+    #[test]
+    fn it_fires_timers_on_the_pool() {
+        let pool = Arc::new(
+            ThreadPoolBuilder::new()
+                .num_threads(1)
+                .thread_name(|index| format!("rayon-{}", index))
+                .build()
+                .unwrap(),
+        );
+        let (fired_tx, fired_rx) = mpsc::channel();
+        pool.schedule_after(Duration::from_millis(5), move || {
+            let name = thread::current().name().map(str::to_string);
+            fired_tx.send(name).unwrap();
+        });
+        assert_eq!(fired_rx.recv().unwrap(), Some("rayon-0".to_string()));
+    }
+}
@@ -1,10 +1,9 @@
+use crate::notification::Notification;
 use crate::observable::Observable;
 use crate::scheduler::Scheduler;
-use log::{error, trace};
-use std::io;
-use std::io::ErrorKind;
-use std::sync::mpsc;
-use std::sync::mpsc::Sender;
+use crate::subscriber::Subscriber;
+use crate::utils;
+use std::ops::ControlFlow;
 
 pub struct ReduceOp<Source, CollectResult, ReduceFunction> {
     pub(crate) source: Source,
@@ -17,33 +16,37 @@ impl<Source, CollectResult, ReduceFunction> Observable
 where
     Source: Observable,
     Source::Item: Send + 'static,
+    Source::Error: Send + 'static,
     ReduceFunction: Fn(CollectResult, Source::Item) -> CollectResult + Send + 'static,
     CollectResult: Send + 'static,
 {
     type Item = CollectResult;
+    type Error = Source::Error;
 
-    fn actual_subscribe<O>(mut self, channel: Sender<io::Result<Self::Item>>, pool: O)
+    fn actual_subscribe<O>(self, channel: Subscriber<Self::Item, Self::Error>, pool: O)
     where
         O: Scheduler + Clone + Send + 'static,
     {
-        let (incoming_tx, incoming_rx) = mpsc::channel::<io::Result<Source::Item>>();
-        pool.schedule(move || {
-            loop {
-                let message = incoming_rx.recv();
-                match message {
-                    Ok(Ok(message)) => self.collector = (self.func)(self.collector, message),
-                    Ok(Err(e)) => {
-                        error!("Reduce, inner unwrap: {:?}", e.to_string());
-                        channel
-                            .send(Err(io::Error::new(ErrorKind::Other, e)))
-                            .unwrap();
-                        break;
-                    }
-                    Err(_) => break, // Channel closed
+        let (incoming_tx, incoming_rx) = channel.upstream::<Source::Item, Source::Error>();
+        let func = self.func;
+        let mut collector = Some(self.collector);
+        utils::receive(&pool, incoming_rx, "Reduce", move |message| match message {
+            Ok(Notification::Next(message)) => {
+                collector = collector.take().map(|collector| (func)(collector, message));
+                ControlFlow::Continue(())
+            }
+            Ok(Notification::Error(e)) => {
+                let _ = channel.send(Notification::Error(e));
+                ControlFlow::Break(())
+            }
+            Ok(Notification::Complete) => {
+                if let Some(collector) = collector.take() {
+                    let _ = channel.send(Notification::Next(collector));
                 }
+                let _ = channel.send(Notification::Complete);
+                ControlFlow::Break(())
             }
-            channel.send(Ok(self.collector)).unwrap();
-            trace!("Reduce finished");
+            Err(_) => ControlFlow::Break(()), // Upstream terminated without completing
         })
         .forget();
         self.source.actual_subscribe(incoming_tx, pool);
@@ -52,9 +55,12 @@ where
 
 #[cfg(test)]
 mod tests {
+    use crate::create::create;
     use crate::from_iter::from_iter;
     use crate::observable::Observable;
+    use crate::observer::Observer;
     use futures::executor::ThreadPool;
+    use std::convert::Infallible;
     use std::sync::atomic::{AtomicI32, Ordering};
     use std::sync::Arc;
 
@@ -71,4 +77,23 @@ mod tests {
         futures::executor::block_on(handle);
         assert_eq!(collector_c.load(Ordering::Relaxed), 45);
     }
+
+    #[test]
+    fn it_does_not_reduce_if_upstream_panicked() {
+        let collector = Arc::new(AtomicI32::new(0));
+        let collector_c = collector.clone();
+        let handle = create::<_, _, Infallible>(|sender| {
+            sender.next(1).unwrap();
+            panic!("Producer failed");
+        })
+        .reduce(0, |c, v| c + v)
+        .subscribe(
+            move |v| {
+                collector.fetch_add(v + 1, Ordering::Relaxed);
+            },
+            ThreadPool::new().unwrap(),
+        );
+        futures::executor::block_on(handle);
+        assert_eq!(collector_c.load(Ordering::Relaxed), 0);
+    }
 }
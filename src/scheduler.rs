@@ -1,35 +1,215 @@
+use crate::clock::{Clock, SystemClock};
+use crate::timer;
+use futures::channel::oneshot;
 use futures::executor::ThreadPool;
 use futures::future::{AbortHandle, RemoteHandle};
 use futures::FutureExt;
-use futures::StreamExt;
-use std::time::Duration;
+use std::panic::{catch_unwind, resume_unwind, AssertUnwindSafe};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Tells a loop step whether it may block waiting for its next message
+pub enum Wait {
+    Block,
+    Poll,
+}
+
+pub enum LoopStep<R> {
+    /// The step handled a message
+    Received,
+    /// There was no message to handle
+    Empty,
+    Finished(R),
+}
+
+/// A loop step whose result type was erased, see `DynScheduler`
+pub type BoxedStep = Box<dyn FnMut(Wait) -> LoopStep<()> + Send>;
+
+/// Erases the result type of a loop, which is handed to the returned handle instead,
+/// like any panic of the loop
+pub(crate) fn box_loop<R>(
+    mut step: impl FnMut(Wait) -> LoopStep<R> + Send + 'static,
+) -> (BoxedStep, RemoteHandle<R>)
+where
+    R: Send + 'static,
+{
+    let (result_tx, result_rx) = oneshot::channel::<thread::Result<R>>();
+    let (remote, remote_handle) = result_rx
+        .map(
+            |result| match result.expect("Loop dropped before finishing") {
+                Ok(result) => result,
+                Err(panic) => resume_unwind(panic),
+            },
+        )
+        .remote_handle();
+    let mut finish = Some((result_tx, remote));
+    let step = Box::new(move |wait| {
+        let result = match catch_unwind(AssertUnwindSafe(|| step(wait))) {
+            Ok(LoopStep::Received) => return LoopStep::Received,
+            Ok(LoopStep::Empty) => return LoopStep::Empty,
+            Ok(LoopStep::Finished(result)) => Ok(result),
+            Err(panic) => Err(panic),
+        };
+        if let Some((result_tx, remote)) = finish.take() {
+            let _ = result_tx.send(result);
+            let _ = remote.now_or_never();
+        }
+        LoopStep::Finished(())
+    });
+    (step, remote_handle)
+}
 
 pub trait Scheduler {
-    fn schedule(&self, task: impl FnOnce() + Send + 'static) -> RemoteHandle<()>;
+    fn schedule<R>(&self, task: impl FnOnce() -> R + Send + 'static) -> RemoteHandle<R>
+    where
+        R: Send + 'static;
+    /// Runs `task` without a handle to wait for it, like the tasks operators spawn per value
+    fn schedule_detached(&self, task: impl FnOnce() + Send + 'static) {
+        self.schedule(task).forget();
+    }
+    /// Runs `step` until it finishes. Schedulers which cannot afford to block a thread per loop
+    /// call it with `Wait::Poll` and revisit it later if it had nothing to do.
+    fn schedule_loop<R>(
+        &self,
+        mut step: impl FnMut(Wait) -> LoopStep<R> + Send + 'static,
+    ) -> RemoteHandle<R>
+    where
+        R: Send + 'static,
+    {
+        self.schedule(move || loop {
+            if let LoopStep::Finished(result) = step(Wait::Block) {
+                break result;
+            }
+        })
+    }
     fn schedule_repeating<F>(&self, task: F, interval: Duration) -> AbortHandle
     where
         F: Fn() + Send + 'static;
+    /// Runs `task` once after `delay`, unless aborted before
+    fn schedule_after<F>(&self, delay: Duration, task: F) -> AbortHandle
+    where
+        F: FnOnce() + Send + 'static;
+    /// Runs `task` once `clock` reaches `time`, unless aborted before
+    fn schedule_at<F>(&self, time: Duration, task: F) -> AbortHandle
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        self.schedule_after(time.saturating_sub(self.clock().now()), task)
+    }
+    /// The clock time based operators running on this scheduler read the current time from
+    fn clock(&self) -> Arc<dyn Clock + Send + Sync> {
+        Arc::new(SystemClock)
+    }
 }
 
 impl Scheduler for ThreadPool {
-    fn schedule(&self, task: impl FnOnce() + Send + 'static) -> RemoteHandle<()> {
+    fn schedule<R>(&self, task: impl FnOnce() -> R + Send + 'static) -> RemoteHandle<R>
+    where
+        R: Send + 'static,
+    {
         let future = async { (task)() };
         let (remote, remote_handle) = future.remote_handle();
         self.spawn_ok(remote);
         remote_handle
     }
 
+    /// Fires on the timer thread, which hands the task to the pool
+    fn schedule_repeating<F>(&self, task: F, interval: Duration) -> AbortHandle
+    where
+        F: Fn() + Send + 'static,
+    {
+        let pool = self.clone();
+        let task = Arc::new(Mutex::new(task));
+        timer::schedule_timer(Instant::now() + interval, Some(interval), move || {
+            let task = task.clone();
+            pool.spawn_ok(async move { (task.lock().unwrap())() });
+        })
+    }
+
+    fn schedule_after<F>(&self, delay: Duration, task: F) -> AbortHandle
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        let pool = self.clone();
+        let mut task = Some(task);
+        timer::schedule_timer(Instant::now() + delay, None, move || {
+            if let Some(task) = task.take() {
+                pool.spawn_ok(async move { (task)() });
+            }
+        })
+    }
+}
+
+impl<S> Scheduler for Arc<S>
+where
+    S: Scheduler + ?Sized,
+{
+    fn schedule<R>(&self, task: impl FnOnce() -> R + Send + 'static) -> RemoteHandle<R>
+    where
+        R: Send + 'static,
+    {
+        (**self).schedule(task)
+    }
+
+    fn schedule_detached(&self, task: impl FnOnce() + Send + 'static) {
+        (**self).schedule_detached(task)
+    }
+
+    fn schedule_loop<R>(
+        &self,
+        step: impl FnMut(Wait) -> LoopStep<R> + Send + 'static,
+    ) -> RemoteHandle<R>
+    where
+        R: Send + 'static,
+    {
+        (**self).schedule_loop(step)
+    }
+
     fn schedule_repeating<F>(&self, task: F, interval: Duration) -> AbortHandle
     where
         F: Fn() + Send + 'static,
     {
-        let abortable = futures::future::abortable(futures::future::ready(()).then(move |_| {
-            async_std::stream::interval(interval).for_each(move |_| {
-                (task)();
-                futures::future::ready(())
-            })
-        }));
-        self.spawn_ok(abortable.0.map(|_| ()));
-        abortable.1
+        (**self).schedule_repeating(task, interval)
+    }
+
+    fn schedule_after<F>(&self, delay: Duration, task: F) -> AbortHandle
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        (**self).schedule_after(delay, task)
+    }
+
+    fn schedule_at<F>(&self, time: Duration, task: F) -> AbortHandle
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        (**self).schedule_at(time, task)
+    }
+
+    fn clock(&self) -> Arc<dyn Clock + Send + Sync> {
+        (**self).clock()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::scheduler::Scheduler;
+    use futures::executor::ThreadPool;
+    use std::sync::mpsc;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn it_schedules_after_a_delay() {
+        let pool = ThreadPool::new().unwrap();
+        let (tx, rx) = mpsc::channel();
+        let tx_c = tx.clone();
+        let start = Instant::now();
+        pool.schedule_after(Duration::from_millis(20), move || tx.send(1).unwrap());
+        pool.schedule_after(Duration::from_millis(10), move || tx_c.send(0).unwrap())
+            .abort();
+        assert_eq!(rx.recv().unwrap(), 1);
+        assert!(start.elapsed() >= Duration::from_millis(20));
+        assert!(rx.recv().is_err());
     }
 }
@@ -1,18 +1,19 @@
+use crate::clock::{Clock, SystemClock};
+use crate::notification::Notification;
 use crate::observable::Observable;
 use crate::scheduler::Scheduler;
-use log::{error, trace};
-use std::io;
-use std::io::ErrorKind;
-use std::sync::mpsc::Sender;
-use std::sync::{mpsc, Arc, Mutex};
-use std::time::{Duration, SystemTime};
+use crate::subscriber::Subscriber;
+use crate::utils;
+use std::ops::ControlFlow;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 pub struct SlidingWindowObservable<Source, Item, TimeFunction> {
     pub(crate) source: Source,
     pub(crate) interval: Duration,
     pub(crate) window_size: Duration,
     pub(crate) time_function: TimeFunction,
-    pub(crate) buffer: Arc<Mutex<Vec<Item>>>,
+    pub(crate) buffer: Arc<Mutex<Option<Vec<Item>>>>,
 }
 
 impl<Source, TimeFunction> Observable
@@ -20,100 +21,136 @@ impl<Source, TimeFunction> Observable
 where
     Source: Observable,
     Source::Item: Send + 'static + Clone,
+    Source::Error: Send + 'static,
     TimeFunction: Fn(&Source::Item) -> Duration + Send + 'static + Clone,
 {
     type Item = Vec<Source::Item>;
+    type Error = Source::Error;
 
-    fn actual_subscribe<O>(self, channel: Sender<io::Result<Self::Item>>, pool: O)
+    fn actual_subscribe<O>(self, channel: Subscriber<Self::Item, Self::Error>, pool: O)
     where
         O: Scheduler + Clone + Send + 'static,
     {
-        let (incoming_tx, incoming_rx) = mpsc::channel::<io::Result<Source::Item>>();
+        let (incoming_tx, incoming_rx) = channel.upstream::<Source::Item, Source::Error>();
         let channel_c = channel.clone();
         let buffer_c = self.buffer.clone();
         let buffer_cc = self.buffer.clone();
         let time_function_c = self.time_function.clone();
+        let clock = pool.clock();
+        let clock_c = clock.clone();
         let handle = pool.schedule_repeating(
             move || {
-                let mut unlocked_buffer = self.buffer.lock().unwrap();
-                unlocked_buffer
-                    .retain(|v| (self.time_function)(v) + self.window_size > get_now_duration());
-                let copied_buffer = unlocked_buffer.clone();
-                channel.send(Ok(copied_buffer)).unwrap();
+                // The buffer is taken once the window is finished, no window may be sent after that
+                if let Some(unlocked_buffer) = self.buffer.lock().unwrap().as_mut() {
+                    unlocked_buffer
+                        .retain(|v| (self.time_function)(v) + self.window_size > clock.now());
+                    let copied_buffer = unlocked_buffer.clone();
+                    let _ = channel.send(Notification::Next(copied_buffer));
+                }
             },
             self.interval,
         );
-        pool.schedule(move || {
-            loop {
-                let message = incoming_rx.recv();
-                match message {
-                    Ok(Ok(message)) => buffer_c.lock().unwrap().push(message),
-                    Ok(Err(e)) => {
-                        error!("Sliding window, inner unwrap: {:?}", e.to_string());
-                        channel_c
-                            .send(Err(io::Error::new(ErrorKind::Other, e)))
-                            .unwrap();
-                        break;
+        let handle_c = handle.clone();
+        channel_c.add_teardown(move || handle_c.abort());
+        let window_size = self.window_size;
+        utils::receive(
+            &pool,
+            incoming_rx,
+            "Sliding window",
+            move |message| match message {
+                Ok(Notification::Next(message)) => {
+                    if let Some(unlocked_buffer) = buffer_c.lock().unwrap().as_mut() {
+                        unlocked_buffer.push(message)
                     }
-                    Err(_) => {
-                        let mut unlocked_buffer = buffer_cc.lock().unwrap();
-                        unlocked_buffer.retain(|v| {
-                            (time_function_c)(v) + self.window_size > get_now_duration()
-                        });
-                        let copied_buffer = unlocked_buffer.iter().cloned().collect();
-                        channel_c.send(Ok(copied_buffer)).unwrap();
-                        handle.abort();
-                        break;
-                    } // Channel closed
+                    ControlFlow::Continue(())
+                }
+                Ok(Notification::Error(e)) => {
+                    buffer_cc.lock().unwrap().take();
+                    handle.abort();
+                    let _ = channel_c.send(Notification::Error(e));
+                    ControlFlow::Break(())
+                }
+                Ok(Notification::Complete) => {
+                    let mut unlocked_buffer = buffer_cc.lock().unwrap().take().unwrap();
+                    handle.abort();
+                    unlocked_buffer.retain(|v| (time_function_c)(v) + window_size > clock_c.now());
+                    let _ = channel_c.send(Notification::Next(unlocked_buffer));
+                    let _ = channel_c.send(Notification::Complete);
+                    ControlFlow::Break(())
                 }
-            }
-            trace!("Sliding window finished");
-        })
+                Err(_) => {
+                    buffer_cc.lock().unwrap().take();
+                    handle.abort();
+                    ControlFlow::Break(())
+                } // Upstream terminated without completing
+            },
+        )
         .forget();
         self.source.actual_subscribe(incoming_tx, pool);
     }
 }
 
 pub fn get_now_duration() -> Duration {
-    SystemTime::now()
-        .duration_since(std::time::UNIX_EPOCH)
-        .expect("Could not get epoch seconds")
+    SystemClock.now()
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::create::create;
     use crate::observable::Observable;
     use crate::observer::Observer;
-    use crate::sliding_window::get_now_duration;
-    use futures::executor::ThreadPool;
+    use crate::scheduler::Scheduler;
+    use crate::subscriber::Subscriber;
+    use crate::test_scheduler::TestScheduler;
+    use std::convert::Infallible;
     use std::sync::{Arc, Mutex};
     use std::time::Duration;
 
+    type Slot = Arc<Mutex<Option<Subscriber<(i32, Duration), Infallible>>>>;
+
+    /// Hands its subscriber to the test, which emits into it between advancing the clock
+    struct Manual(Slot);
+
+    impl Observable for Manual {
+        type Item = (i32, Duration);
+        type Error = Infallible;
+
+        fn actual_subscribe<O>(self, channel: Subscriber<Self::Item, Self::Error>, _: O)
+        where
+            O: Scheduler + Clone + Send + 'static,
+        {
+            *self.0.lock().unwrap() = Some(channel);
+        }
+    }
+
     #[test]
     fn it_buffers_in_sliding_windows() {
-        let pool = ThreadPool::new().unwrap();
+        let scheduler = TestScheduler::new();
+        let slot: Slot = Arc::new(Mutex::new(None));
 
         let actual = Arc::new(Mutex::new(vec![]));
         let actual_c = actual.clone();
 
         let expected = vec![vec![0], vec![0, 1], vec![1], vec![1, 2], vec![2]];
 
-        let handle = create(|sender| {
-            let sleep = Duration::from_millis(43);
-            sender.next(0).unwrap();
-            std::thread::sleep(sleep);
-            sender.next(1).unwrap();
-            std::thread::sleep(sleep);
-            sender.next(2).unwrap();
-            std::thread::sleep(Duration::from_millis(20));
-        })
-        .map(|v| (v, get_now_duration()))
-        .sliding_window(Duration::from_millis(23), Duration::from_millis(59), |v| {
-            v.1
-        })
-        .map(|window| window.iter().map(|(i, _)| *i).collect::<Vec<i32>>())
-        .subscribe(move |buffer| actual.lock().unwrap().push(buffer), pool);
+        let handle = Manual(slot.clone())
+            .sliding_window(Duration::from_millis(23), Duration::from_millis(59), |v| {
+                v.1
+            })
+            .map(|window| window.iter().map(|(i, _)| *i).collect::<Vec<i32>>())
+            .subscribe(
+                move |buffer| actual.lock().unwrap().push(buffer),
+                scheduler.clone(),
+            );
+        let sender = slot.lock().unwrap().take().unwrap();
+        let sleep = Duration::from_millis(43);
+        sender.next((0, scheduler.now())).unwrap();
+        scheduler.advance_by(sleep);
+        sender.next((1, scheduler.now())).unwrap();
+        scheduler.advance_by(sleep);
+        sender.next((2, scheduler.now())).unwrap();
+        scheduler.advance_by(Duration::from_millis(20));
+        sender.complete().unwrap();
+        scheduler.run();
 
         futures::executor::block_on(handle);
         assert_eq!(expected, *actual_c.lock().unwrap());
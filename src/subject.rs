@@ -0,0 +1,467 @@
+use crate::clock::Clock;
+use crate::notification::Notification;
+use crate::observable::Observable;
+use crate::observer::{Observer, ObserverResult};
+use crate::scheduler::Scheduler;
+use crate::subscriber::Subscriber;
+use crate::utils;
+use std::collections::VecDeque;
+use std::marker::PhantomData;
+use std::sync::mpsc::SendError;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Emits every notification it is sent to all observables subscribed at that time, from any thread.
+/// Subscribing after it terminated only emits the terminating notification.
+/// `Kind` selects what new subscribers receive first, see `PublishSubject`, `BehaviorSubject` and `ReplaySubject`.
+/// Notifications sent from several threads at once may reach the subscribers in different orders.
+pub struct Subject<Item, E, Kind> {
+    inner: Arc<Mutex<Multicast<Item, E>>>,
+    kind: PhantomData<Kind>,
+}
+
+/// Only emits what is sent after subscribing
+pub type PublishSubject<Item, E> = Subject<Item, E, Publish>;
+
+/// New subscribers first receive the latest value, starting with the initial one
+pub type BehaviorSubject<Item, E> = Subject<Item, E, Behavior>;
+
+/// New subscribers first receive the values emitted before,
+/// limited to the last `buffer_size` values and/or those emitted within the last `window`
+pub type ReplaySubject<Item, E> = Subject<Item, E, Replay>;
+
+pub enum Publish {}
+
+pub enum Behavior {}
+
+pub enum Replay {}
+
+struct Multicast<Item, E> {
+    /// Keyed by the order they subscribed in
+    subscribers: Vec<(u64, Subscriber<Item, E>)>,
+    /// Subscribers still being sent what they replay, with what was emitted since they subscribed
+    replaying: Vec<(u64, Vec<Notification<Item, E>>)>,
+    subscribed: u64,
+    replayed: Replayed<Item>,
+    terminal: Option<Notification<Item, E>>,
+}
+
+enum Replayed<Item> {
+    Nothing,
+    Latest(Item),
+    Buffer {
+        /// The values together with the time they were emitted at
+        values: VecDeque<(Duration, Item)>,
+        buffer_size: Option<usize>,
+        window: Option<(Duration, Arc<dyn Clock + Send + Sync>)>,
+    },
+}
+
+impl<Item, E> PublishSubject<Item, E> {
+    pub fn new() -> Self {
+        Subject::with_replayed(Replayed::Nothing)
+    }
+}
+
+impl<Item, E> Default for PublishSubject<Item, E> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<Item, E> BehaviorSubject<Item, E>
+where
+    Item: Clone,
+{
+    pub fn new(initial: Item) -> Self {
+        Subject::with_replayed(Replayed::Latest(initial))
+    }
+
+    pub fn value(&self) -> Item {
+        match &self.inner.lock().unwrap().replayed {
+            Replayed::Latest(value) => value.clone(),
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl<Item, E> ReplaySubject<Item, E> {
+    pub fn new(buffer_size: usize) -> Self {
+        Self::with_limits(Some(buffer_size), None)
+    }
+
+    /// Replays the values emitted within the last `window`, as measured by `clock`, e.g. the clock of a `Scheduler`
+    pub fn with_window(window: Duration, clock: Arc<dyn Clock + Send + Sync>) -> Self {
+        Self::with_limits(None, Some((window, clock)))
+    }
+
+    pub fn with_limits(
+        buffer_size: Option<usize>,
+        window: Option<(Duration, Arc<dyn Clock + Send + Sync>)>,
+    ) -> Self {
+        Subject::with_replayed(Replayed::Buffer {
+            values: VecDeque::new(),
+            buffer_size,
+            window,
+        })
+    }
+}
+
+impl<Item, E, Kind> Subject<Item, E, Kind> {
+    fn with_replayed(replayed: Replayed<Item>) -> Self {
+        Subject {
+            inner: Arc::new(Mutex::new(Multicast {
+                subscribers: vec![],
+                replaying: vec![],
+                subscribed: 0,
+                replayed,
+                terminal: None,
+            })),
+            kind: PhantomData,
+        }
+    }
+
+    /// Whether it was sent an `Error` or `Complete`
+    pub fn is_terminated(&self) -> bool {
+        self.inner.lock().unwrap().terminal.is_some()
+    }
+}
+
+impl<Item, E, Kind> Subject<Item, E, Kind>
+where
+    Item: Clone,
+    E: Clone,
+{
+    /// Sends to the subscribers without holding the lock, so a slow one doesn't block anyone else using the subject
+    fn emit(&self, notification: Notification<Item, E>) -> ObserverResult<Item, E> {
+        let subscribers = match self.inner.lock().unwrap().record(&notification) {
+            Some(subscribers) => subscribers,
+            None => return Err(SendError(notification)),
+        };
+        let failed = subscribers
+            .into_iter()
+            .filter(|(_, subscriber)| subscriber.send(notification.clone()).is_err())
+            .map(|(id, _)| id)
+            .collect::<Vec<_>>();
+        if !failed.is_empty() {
+            let mut inner = self.inner.lock().unwrap();
+            inner.subscribers.retain(|(id, _)| !failed.contains(id));
+        }
+        Ok(())
+    }
+}
+
+impl<Item, E> Multicast<Item, E>
+where
+    Item: Clone,
+    E: Clone,
+{
+    /// Returns the subscribers to send `notification` to, or `None` if it terminated before
+    fn record(
+        &mut self,
+        notification: &Notification<Item, E>,
+    ) -> Option<Vec<(u64, Subscriber<Item, E>)>> {
+        if self.terminal.is_some() {
+            return None;
+        }
+        for (_, queued) in &mut self.replaying {
+            queued.push(notification.clone());
+        }
+        match notification {
+            Notification::Next(value) => {
+                self.replayed.push(value.clone());
+                Some(self.subscribers.clone())
+            }
+            _ => {
+                self.terminal = Some(notification.clone());
+                Some(std::mem::take(&mut self.subscribers))
+            }
+        }
+    }
+
+    /// Registers `channel`, returning its id and what it has to be sent first, if anything.
+    /// Until that is sent, whatever is emitted meanwhile is queued in `replaying`.
+    fn subscribe(&mut self, channel: Subscriber<Item, E>) -> Option<Pending<Item, E>> {
+        let mut replayed = match &self.terminal {
+            // Only values emitted before it terminated count for behavior subjects
+            Some(_) if matches!(self.replayed, Replayed::Latest(_)) => vec![],
+            _ => self.replayed.values(),
+        }
+        .into_iter()
+        .map(Notification::Next)
+        .collect::<Vec<_>>();
+        replayed.extend(self.terminal.clone());
+        let id = self.subscribed;
+        self.subscribed += 1;
+        if replayed.is_empty() {
+            self.subscribers.push((id, channel));
+            return None;
+        }
+        if self.terminal.is_none() {
+            self.replaying.push((id, vec![]));
+        }
+        Some(Pending {
+            id,
+            channel,
+            pending: replayed,
+        })
+    }
+}
+
+/// What a new subscriber has to be sent before it receives what is emitted
+struct Pending<Item, E> {
+    id: u64,
+    channel: Subscriber<Item, E>,
+    pending: Vec<Notification<Item, E>>,
+}
+
+/// Sends `pending` to a new subscriber, then whatever was emitted meanwhile, before handing it over to `emit`
+fn replay<Item, E>(inner: &Mutex<Multicast<Item, E>>, replayed: Pending<Item, E>)
+where
+    Item: Send + 'static,
+    E: Send + 'static,
+{
+    let Pending {
+        id,
+        channel,
+        mut pending,
+    } = replayed;
+    loop {
+        for notification in pending {
+            if channel.send(notification).is_err() {
+                // Unsubscribed
+                inner.lock().unwrap().replaying.retain(|(i, _)| *i != id);
+                return;
+            }
+        }
+        let mut inner = inner.lock().unwrap();
+        // Not queued at all if the subject had terminated before
+        let Some(index) = inner.replaying.iter().position(|(i, _)| *i == id) else {
+            return;
+        };
+        pending = std::mem::take(&mut inner.replaying[index].1);
+        if pending.is_empty() {
+            inner.replaying.remove(index);
+            if inner.terminal.is_none() {
+                inner.subscribers.push((id, channel));
+            }
+            return;
+        }
+    }
+}
+
+impl<Item> Replayed<Item>
+where
+    Item: Clone,
+{
+    fn push(&mut self, value: Item) {
+        match self {
+            Replayed::Nothing => {}
+            Replayed::Latest(latest) => *latest = value,
+            Replayed::Buffer {
+                values,
+                buffer_size,
+                window,
+            } => {
+                let now = window
+                    .as_ref()
+                    .map_or(Duration::ZERO, |(_, clock)| clock.now());
+                values.push_back((now, value));
+                if let Some(buffer_size) = buffer_size {
+                    while values.len() > *buffer_size {
+                        values.pop_front();
+                    }
+                }
+            }
+        }
+    }
+
+    fn values(&mut self) -> Vec<Item> {
+        match self {
+            Replayed::Nothing => vec![],
+            Replayed::Latest(latest) => vec![latest.clone()],
+            Replayed::Buffer { values, window, .. } => {
+                if let Some((window, clock)) = window {
+                    let start = clock.now().saturating_sub(*window);
+                    while values.front().is_some_and(|(time, _)| *time < start) {
+                        values.pop_front();
+                    }
+                }
+                values.iter().map(|(_, value)| value.clone()).collect()
+            }
+        }
+    }
+}
+
+impl<Item, E, Kind> Clone for Subject<Item, E, Kind> {
+    fn clone(&self) -> Self {
+        Subject {
+            inner: self.inner.clone(),
+            kind: PhantomData,
+        }
+    }
+}
+
+impl<Item, E, Kind> Observer for Subject<Item, E, Kind>
+where
+    Item: Clone,
+    E: Clone,
+{
+    type Item = Item;
+    type Error = E;
+
+    fn next(&self, value: Self::Item) -> ObserverResult<Item, E> {
+        self.emit(Notification::Next(value))
+    }
+
+    fn error(&self, err: Self::Error) -> ObserverResult<Item, E> {
+        self.emit(Notification::Error(err))
+    }
+
+    fn complete(&self) -> ObserverResult<Item, E> {
+        self.emit(Notification::Complete)
+    }
+}
+
+impl<Item, E, Kind> Observable for Subject<Item, E, Kind>
+where
+    Item: Clone + Send + 'static,
+    E: Clone + Send + 'static,
+{
+    type Item = Item;
+    type Error = E;
+
+    fn actual_subscribe<O>(self, channel: Subscriber<Self::Item, Self::Error>, pool: O)
+    where
+        O: Scheduler + Clone + Send + 'static,
+    {
+        let replayed = self.inner.lock().unwrap().subscribe(channel);
+        if let Some(replayed) = replayed {
+            // Sent from a task of its own, as a bounded channel blocks until the loops downstream run
+            let inner = self.inner;
+            utils::schedule_blocking(&pool, move || replay(&inner, replayed)).forget();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::observable::Observable;
+    use crate::observer::Observer;
+    use crate::scheduler::Scheduler;
+    use crate::subject::{BehaviorSubject, PublishSubject, ReplaySubject};
+    use crate::test_scheduler::TestScheduler;
+    use futures::executor::{block_on, ThreadPool};
+    use std::sync::{mpsc, Mutex};
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn it_publishes_to_all_subscribers() {
+        let pool = ThreadPool::new().unwrap();
+        let subject = PublishSubject::<i32, String>::new();
+        subject.next(0).unwrap();
+        let first = subject.clone().to_vec(pool.clone());
+        subject.next(1).unwrap();
+        let second = subject.clone().map_inline(|v| v * 10).to_vec(pool.clone());
+        subject.next(2).unwrap();
+        subject.complete().unwrap();
+        assert!(subject.next(3).is_err());
+        assert_eq!(block_on(first), Ok(vec![1, 2]));
+        assert_eq!(block_on(second), Ok(vec![20]));
+        assert_eq!(block_on(subject.to_vec(pool)), Ok(vec![]));
+    }
+
+    #[test]
+    fn it_emits_the_latest_value_first() {
+        let pool = ThreadPool::new().unwrap();
+        let subject = BehaviorSubject::<i32, String>::new(0);
+        let initial = subject.clone().first(pool.clone());
+        subject.next(1).unwrap();
+        let latest = subject.clone().first(pool.clone());
+        let all = subject.clone().to_vec(pool.clone());
+        subject.next(2).unwrap();
+        assert_eq!(subject.value(), 2);
+        subject.complete().unwrap();
+        assert_eq!(block_on(initial), Ok(Some(0)));
+        assert_eq!(block_on(latest), Ok(Some(1)));
+        assert_eq!(block_on(all), Ok(vec![1, 2]));
+        assert_eq!(block_on(subject.to_vec(pool)), Ok(vec![]));
+    }
+
+    #[test]
+    fn it_replays_values() {
+        let pool = ThreadPool::new().unwrap();
+        let subject = ReplaySubject::<i32, String>::new(2);
+        for i in 0..5 {
+            subject.next(i).unwrap();
+        }
+        subject.complete().unwrap();
+        assert_eq!(block_on(subject.to_vec(pool.clone())), Ok(vec![3, 4]));
+
+        let scheduler = TestScheduler::new();
+        let subject =
+            ReplaySubject::<i32, String>::with_window(Duration::from_millis(10), scheduler.clock());
+        subject.next(0).unwrap();
+        scheduler.advance_by(Duration::from_millis(5));
+        subject.next(1).unwrap();
+        scheduler.advance_by(Duration::from_millis(6));
+        subject.next(2).unwrap();
+        subject.complete().unwrap();
+        assert_eq!(block_on(subject.to_vec(pool)), Ok(vec![1, 2]));
+    }
+
+    #[test]
+    fn it_replays_into_bounded_pipelines() {
+        let pool = ThreadPool::new().unwrap();
+        let subject = ReplaySubject::<i32, String>::new(10);
+        for i in 0..5 {
+            subject.next(i).unwrap();
+        }
+        let replayed = subject
+            .clone()
+            .map_inline(|v| v)
+            .bounded(1)
+            .to_vec(pool.clone());
+        subject.next(5).unwrap();
+        subject.complete().unwrap();
+        assert_eq!(block_on(replayed), Ok(vec![0, 1, 2, 3, 4, 5]));
+
+        let subject = BehaviorSubject::<i32, String>::new(0);
+        let latest = subject.clone().map_inline(|v| v + 1).bounded(0).first(pool);
+        assert_eq!(block_on(latest), Ok(Some(1)));
+    }
+
+    #[test]
+    fn it_serves_other_subscribers_while_one_is_slow() {
+        let pool = ThreadPool::new().unwrap();
+        let subject = BehaviorSubject::<i32, String>::new(0);
+        let (release_tx, release_rx) = mpsc::channel::<()>();
+        let release_rx = Mutex::new(release_rx);
+        let slow = subject
+            .clone()
+            .map_inline(move |v| {
+                let _ = release_rx.lock().unwrap().recv();
+                v
+            })
+            .bounded(1)
+            .to_vec(pool.clone());
+        let subject_c = subject.clone();
+        let producer = thread::spawn(move || {
+            for i in 1..5 {
+                subject_c.next(i).unwrap();
+            }
+        });
+        // The slow subscriber holds 0 and keeps the producer from going much further
+        while subject.value() < 2 {
+            thread::sleep(Duration::from_millis(1));
+        }
+        thread::sleep(Duration::from_millis(10));
+        let latest = block_on(subject.clone().first(pool)).unwrap().unwrap();
+        assert!((2..5).contains(&latest));
+        drop(release_tx);
+        producer.join().unwrap();
+        subject.complete().unwrap();
+        assert_eq!(block_on(slow), Ok(vec![0, 1, 2, 3, 4]));
+    }
+}
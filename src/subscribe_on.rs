@@ -1,9 +1,8 @@
+use crate::deferred_scheduler::DeferredScheduler;
 use crate::observable::Observable;
 use crate::scheduler::Scheduler;
+use crate::subscriber::Subscriber;
 use crate::utils;
-use std::io;
-use std::sync::mpsc;
-use std::sync::mpsc::Sender;
 
 pub struct SubscribeOnObservable<Source, Pool> {
     pub(crate) source: Source,
@@ -14,16 +13,21 @@ impl<Source, Pool> Observable for SubscribeOnObservable<Source, Pool>
 where
     Source: Observable,
     Source::Item: Send + 'static,
+    Source::Error: Send + 'static,
     Pool: Scheduler + Clone + Send + 'static,
 {
     type Item = Source::Item;
+    type Error = Source::Error;
 
-    fn actual_subscribe<O>(self, channel: Sender<io::Result<Self::Item>>, pool: O)
+    fn actual_subscribe<O>(self, channel: Subscriber<Self::Item, Self::Error>, pool: O)
     where
         O: Scheduler + Clone + Send + 'static,
     {
-        let (incoming_tx, incoming_rx) = mpsc::channel::<io::Result<Source::Item>>();
+        let (incoming_tx, incoming_rx) = channel.upstream::<Source::Item, Source::Error>();
+        // Handed on like by `subscribe`, so an `observe_on` above can still move the operators below
+        let deferred = DeferredScheduler::above(&pool);
         utils::forward_messages(incoming_rx, channel, pool);
-        self.source.actual_subscribe(incoming_tx, self.pool);
+        self.source.actual_subscribe(incoming_tx, deferred.clone());
+        deferred.resolve_upstream(self.pool);
     }
 }
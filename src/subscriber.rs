@@ -0,0 +1,141 @@
+use crate::notification::Notification;
+use crate::observer::{Observer, ObserverResult};
+use crate::subscription::SubscriptionState;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc;
+use std::sync::mpsc::{Receiver, SendError, Sender, SyncSender};
+use std::sync::Arc;
+
+/// The sending end of the channel an observable emits its notifications into,
+/// tied to the subscription of the pipeline it is part of.
+/// Anything sent after the first `Error` or `Complete` is dropped.
+pub struct Subscriber<Item, E> {
+    sender: ChannelSender<Notification<Item, E>>,
+    state: Arc<SubscriptionState>,
+    capacity: Option<usize>,
+    terminated: Arc<AtomicBool>,
+}
+
+enum ChannelSender<T> {
+    Unbounded(Sender<T>),
+    Bounded(SyncSender<T>),
+}
+
+impl<Item, E> Subscriber<Item, E> {
+    /// Creates the channel a pipeline sends its notifications into, bounded to `capacity` if given
+    pub(crate) fn channel(
+        state: Arc<SubscriptionState>,
+        capacity: Option<usize>,
+    ) -> (Self, Receiver<Notification<Item, E>>) {
+        let (sender, receiver) = match capacity {
+            None => {
+                let (sender, receiver) = mpsc::channel();
+                (ChannelSender::Unbounded(sender), receiver)
+            }
+            Some(capacity) => {
+                let (sender, receiver) = mpsc::sync_channel(capacity);
+                (ChannelSender::Bounded(sender), receiver)
+            }
+        };
+        let subscriber = Subscriber {
+            sender,
+            state,
+            capacity,
+            terminated: Arc::default(),
+        };
+        (subscriber, receiver)
+    }
+
+    /// Creates the channel for the next observable upstream, which shares this subscription.
+    /// The channel is bounded if a capacity was set further downstream.
+    pub(crate) fn upstream<T, E2>(&self) -> (Subscriber<T, E2>, Receiver<Notification<T, E2>>) {
+        Subscriber::channel(self.state.clone(), self.capacity)
+    }
+
+    /// Clones this subscriber for another upstream sharing its channel, which terminates on its own
+    pub(crate) fn fork(&self) -> Self {
+        Subscriber {
+            sender: self.sender.clone(),
+            state: self.state.clone(),
+            capacity: self.capacity,
+            terminated: Arc::default(),
+        }
+    }
+
+    /// Makes all channels created upstream of this subscriber hold at most `capacity` notifications
+    pub(crate) fn with_capacity(mut self, capacity: usize) -> Self {
+        self.capacity = Some(capacity);
+        self
+    }
+
+    pub(crate) fn capacity(&self) -> Option<usize> {
+        self.capacity
+    }
+
+    pub(crate) fn send(&self, notification: Notification<Item, E>) -> ObserverResult<Item, E> {
+        if self.is_closed() {
+            return Err(SendError(notification));
+        }
+        let terminated = match notification {
+            Notification::Next(_) => self.terminated.load(Ordering::Acquire),
+            _ => self.terminated.swap(true, Ordering::AcqRel),
+        };
+        if terminated {
+            return Err(SendError(notification));
+        }
+        match &self.sender {
+            ChannelSender::Unbounded(sender) => sender.send(notification),
+            ChannelSender::Bounded(sender) => sender.send(notification),
+        }
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.state.is_closed()
+    }
+
+    /// Registers a function to be run once the subscription is unsubscribed
+    pub fn add_teardown(&self, teardown: impl FnOnce() + Send + 'static) {
+        self.state.add_teardown(teardown);
+    }
+}
+
+impl<Item, E> Clone for Subscriber<Item, E> {
+    fn clone(&self) -> Self {
+        Subscriber {
+            sender: self.sender.clone(),
+            state: self.state.clone(),
+            capacity: self.capacity,
+            terminated: self.terminated.clone(),
+        }
+    }
+}
+
+impl<T> Clone for ChannelSender<T> {
+    fn clone(&self) -> Self {
+        match self {
+            ChannelSender::Unbounded(sender) => ChannelSender::Unbounded(sender.clone()),
+            ChannelSender::Bounded(sender) => ChannelSender::Bounded(sender.clone()),
+        }
+    }
+}
+
+impl<Item, E> Observer for Subscriber<Item, E>
+where
+    Item: Send + 'static,
+    E: Send + 'static,
+{
+    type Item = Item;
+    type Error = E;
+
+    fn next(&self, value: Self::Item) -> ObserverResult<Item, E> {
+        self.send(Notification::Next(value))
+    }
+
+    fn error(&self, err: Self::Error) -> ObserverResult<Item, E> {
+        self.send(Notification::Error(err))
+    }
+
+    fn complete(&self) -> ObserverResult<Item, E> {
+        self.send(Notification::Complete)
+    }
+}
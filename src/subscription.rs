@@ -0,0 +1,92 @@
+use futures::future::RemoteHandle;
+use std::future::Future;
+use std::mem;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+
+type Teardown = Box<dyn FnOnce() + Send>;
+
+#[derive(Default)]
+pub(crate) struct SubscriptionState {
+    closed: AtomicBool,
+    teardowns: Mutex<Vec<Teardown>>,
+}
+
+impl SubscriptionState {
+    pub(crate) fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::Acquire)
+    }
+
+    pub(crate) fn unsubscribe(&self) {
+        let teardowns = {
+            let mut teardowns = self.teardowns.lock().unwrap();
+            if self.closed.swap(true, Ordering::AcqRel) {
+                return;
+            }
+            mem::take(&mut *teardowns)
+        };
+        teardowns.into_iter().for_each(|teardown| teardown());
+    }
+
+    pub(crate) fn add_teardown(&self, teardown: impl FnOnce() + Send + 'static) {
+        {
+            let mut teardowns = self.teardowns.lock().unwrap();
+            if !self.is_closed() {
+                teardowns.push(Box::new(teardown));
+                return;
+            }
+        }
+        teardown();
+    }
+}
+
+/// Handle to a running pipeline, resolving once the subscriber has finished.
+/// Unsubscribing closes the whole pipeline: no further values are delivered, every sender upstream
+/// starts failing, and timers of the operators are aborted.
+pub struct Subscription<T = ()> {
+    handle: RemoteHandle<T>,
+    state: Arc<SubscriptionState>,
+}
+
+/// Why a stream ended without completing
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Terminated<E> {
+    /// The stream was terminated with this error
+    Error(E),
+    /// The subscription was unsubscribed from before the stream completed
+    Unsubscribed,
+    /// The upstream went away without completing, e.g. as a producer panicked,
+    /// or a `share` was subscribed to after it disconnected
+    Dropped,
+}
+
+/// A subscription resolving to a result computed from the stream, or why it ended without completing
+pub type TrySubscription<T, E> = Subscription<Result<T, Terminated<E>>>;
+
+impl<T> Subscription<T> {
+    pub(crate) fn new(handle: RemoteHandle<T>, state: Arc<SubscriptionState>) -> Self {
+        Subscription { handle, state }
+    }
+
+    pub fn unsubscribe(&self) {
+        self.state.unsubscribe();
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.state.is_closed()
+    }
+
+    pub fn forget(self) {
+        self.handle.forget();
+    }
+}
+
+impl<T: 'static> Future for Subscription<T> {
+    type Output = T;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<T> {
+        Pin::new(&mut self.get_mut().handle).poll(cx)
+    }
+}
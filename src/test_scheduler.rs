@@ -0,0 +1,208 @@
+use crate::clock::Clock;
+use crate::current_thread_scheduler::CurrentThreadScheduler;
+use crate::scheduler::{LoopStep, Scheduler, Wait};
+use futures::future::{AbortHandle, RemoteHandle};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// A `CurrentThreadScheduler` with a virtual clock, which only moves when advanced manually.
+/// Repeating tasks fire at their due time on the way, so timer driven operators can be tested without sleeping.
+/// The clock starts at zero.
+#[derive(Clone, Default)]
+pub struct TestScheduler {
+    executor: CurrentThreadScheduler,
+    clock: VirtualClock,
+    timers: Arc<Mutex<Vec<Timer>>>,
+}
+
+#[derive(Clone, Default)]
+struct VirtualClock {
+    now: Arc<Mutex<Duration>>,
+}
+
+impl Clock for VirtualClock {
+    fn now(&self) -> Duration {
+        *self.now.lock().unwrap()
+    }
+}
+
+struct Timer {
+    due: Duration,
+    /// Repeating timers are due again after their interval
+    interval: Option<Duration>,
+    task: Box<dyn FnMut() + Send>,
+    abort_handle: AbortHandle,
+}
+
+impl TestScheduler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn now(&self) -> Duration {
+        self.clock.now()
+    }
+
+    /// Runs the scheduled tasks without moving the clock, see `CurrentThreadScheduler::run`
+    pub fn run(&self) {
+        self.executor.run();
+    }
+
+    pub fn advance_by(&self, duration: Duration) {
+        self.advance_to(self.now() + duration);
+    }
+
+    /// Moves the clock to `time`, firing every repeating task due until then and running the scheduled tasks after each
+    pub fn advance_to(&self, time: Duration) {
+        self.run();
+        while let Some(mut timer) = self.next_timer(time) {
+            *self.clock.now.lock().unwrap() = timer.due;
+            (timer.task)();
+            if let Some(interval) = timer.interval {
+                timer.due += interval;
+                self.timers.lock().unwrap().push(timer);
+            }
+            self.run();
+        }
+        let now = self.now().max(time);
+        *self.clock.now.lock().unwrap() = now;
+        self.run();
+    }
+
+    pub(crate) fn schedule_timer(
+        &self,
+        due: Duration,
+        interval: Option<Duration>,
+        task: impl FnMut() + Send + 'static,
+    ) -> AbortHandle {
+        let (abort_handle, _) = AbortHandle::new_pair();
+        self.timers.lock().unwrap().push(Timer {
+            due,
+            interval,
+            task: Box::new(task),
+            abort_handle: abort_handle.clone(),
+        });
+        abort_handle
+    }
+
+    /// Removes the earliest timer due until `time` which is not aborted
+    fn next_timer(&self, time: Duration) -> Option<Timer> {
+        let mut timers = self.timers.lock().unwrap();
+        timers.retain(|timer| !timer.abort_handle.is_aborted());
+        let (index, _) = timers
+            .iter()
+            .enumerate()
+            .filter(|(_, timer)| timer.due <= time)
+            .min_by_key(|(_, timer)| timer.due)?;
+        Some(timers.remove(index))
+    }
+}
+
+impl Scheduler for TestScheduler {
+    fn schedule<R>(&self, task: impl FnOnce() -> R + Send + 'static) -> RemoteHandle<R>
+    where
+        R: Send + 'static,
+    {
+        self.executor.schedule(task)
+    }
+
+    fn schedule_loop<R>(
+        &self,
+        step: impl FnMut(Wait) -> LoopStep<R> + Send + 'static,
+    ) -> RemoteHandle<R>
+    where
+        R: Send + 'static,
+    {
+        self.executor.schedule_loop(step)
+    }
+
+    fn schedule_repeating<F>(&self, task: F, interval: Duration) -> AbortHandle
+    where
+        F: Fn() + Send + 'static,
+    {
+        self.schedule_timer(self.now() + interval, Some(interval), task)
+    }
+
+    fn schedule_after<F>(&self, delay: Duration, task: F) -> AbortHandle
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        let mut task = Some(task);
+        self.schedule_timer(self.now() + delay, None, move || {
+            if let Some(task) = task.take() {
+                (task)();
+            }
+        })
+    }
+
+    fn clock(&self) -> Arc<dyn Clock + Send + Sync> {
+        Arc::new(self.clock.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::scheduler::Scheduler;
+    use crate::test_scheduler::TestScheduler;
+    use std::sync::atomic::{AtomicI32, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    #[test]
+    fn it_fires_repeating_tasks_in_virtual_time() {
+        let scheduler = TestScheduler::new();
+        let counter = Arc::new(AtomicI32::new(0));
+        let counter_c = counter.clone();
+        let handle = scheduler.schedule_repeating(
+            move || {
+                counter_c.fetch_add(1, Ordering::Relaxed);
+            },
+            Duration::from_millis(10),
+        );
+        scheduler.advance_by(Duration::from_millis(9));
+        assert_eq!(counter.load(Ordering::Relaxed), 0);
+        scheduler.advance_by(Duration::from_millis(26));
+        assert_eq!(counter.load(Ordering::Relaxed), 3);
+        assert_eq!(scheduler.now(), Duration::from_millis(35));
+        handle.abort();
+        scheduler.advance_by(Duration::from_secs(1));
+        assert_eq!(counter.load(Ordering::Relaxed), 3);
+    }
+
+    #[test]
+    fn it_reads_the_virtual_clock() {
+        let scheduler = TestScheduler::new();
+        let clock = scheduler.clock();
+        let times = Arc::new(Mutex::new(vec![]));
+        let times_c = times.clone();
+        let _handle = scheduler.schedule_repeating(
+            move || times_c.lock().unwrap().push(clock.now()),
+            Duration::from_millis(5),
+        );
+        scheduler.advance_to(Duration::from_millis(12));
+        assert_eq!(
+            *times.lock().unwrap(),
+            vec![Duration::from_millis(5), Duration::from_millis(10)]
+        );
+    }
+
+    #[test]
+    fn it_schedules_at_a_virtual_time() {
+        let scheduler = TestScheduler::new();
+        let counter = Arc::new(AtomicI32::new(0));
+        let (counter_c, counter_cc) = (counter.clone(), counter.clone());
+        scheduler.schedule_at(Duration::from_millis(10), move || {
+            counter_c.fetch_add(1, Ordering::Relaxed);
+        });
+        let handle = scheduler.schedule_after(Duration::from_millis(20), move || {
+            counter_cc.fetch_add(10, Ordering::Relaxed);
+        });
+        scheduler.advance_by(Duration::from_millis(9));
+        assert_eq!(counter.load(Ordering::Relaxed), 0);
+        scheduler.advance_by(Duration::from_millis(1));
+        assert_eq!(counter.load(Ordering::Relaxed), 1);
+        handle.abort();
+        scheduler.advance_by(Duration::from_secs(1));
+        assert_eq!(counter.load(Ordering::Relaxed), 1);
+    }
+}
@@ -0,0 +1,134 @@
+use futures::future::AbortHandle;
+use log::error;
+use std::collections::BTreeMap;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::{Condvar, Mutex, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// The timers of all schedulers without timers of their own, fired by one thread in the order they are due
+#[derive(Default)]
+struct Timers {
+    queue: Mutex<Queue>,
+    changed: Condvar,
+}
+
+#[derive(Default)]
+struct Queue {
+    /// Keyed by due time and insertion order
+    timers: BTreeMap<(Instant, u64), Timer>,
+    inserted: u64,
+}
+
+impl Queue {
+    fn insert(&mut self, due: Instant, timer: Timer) {
+        self.timers.insert((due, self.inserted), timer);
+        self.inserted += 1;
+    }
+}
+
+struct Timer {
+    /// Repeating timers are due again after their interval
+    interval: Option<Duration>,
+    task: Box<dyn FnMut() + Send>,
+    abort_handle: AbortHandle,
+}
+
+fn timers() -> &'static Timers {
+    static TIMERS: OnceLock<Timers> = OnceLock::new();
+    TIMERS.get_or_init(|| {
+        thread::Builder::new()
+            .name("rx-timer".to_string())
+            .spawn(|| timers().run())
+            .expect("failed to spawn the timer thread");
+        Timers::default()
+    })
+}
+
+/// Runs `task` on the timer thread at `due`, and every `interval` after if given, until aborted.
+/// The task should only hand the actual work to a scheduler, as it holds up all other timers while running.
+pub(crate) fn schedule_timer(
+    due: Instant,
+    interval: Option<Duration>,
+    task: impl FnMut() + Send + 'static,
+) -> AbortHandle {
+    let (abort_handle, _) = AbortHandle::new_pair();
+    timers().insert(
+        due,
+        Timer {
+            interval,
+            task: Box::new(task),
+            abort_handle: abort_handle.clone(),
+        },
+    );
+    abort_handle
+}
+
+impl Timers {
+    fn insert(&self, due: Instant, timer: Timer) {
+        self.queue.lock().unwrap().insert(due, timer);
+        self.changed.notify_one();
+    }
+
+    fn run(&self) {
+        let mut queue = self.queue.lock().unwrap();
+        loop {
+            let now = Instant::now();
+            let due = match queue.timers.first_key_value() {
+                Some(((due, _), _)) => *due,
+                None => {
+                    queue = self.changed.wait(queue).unwrap();
+                    continue;
+                }
+            };
+            if due > now {
+                queue = self.changed.wait_timeout(queue, due - now).unwrap().0;
+                continue;
+            }
+            let (_, mut timer) = queue.timers.pop_first().unwrap();
+            if timer.abort_handle.is_aborted() {
+                continue;
+            }
+            drop(queue);
+            if catch_unwind(AssertUnwindSafe(&mut timer.task)).is_err() {
+                error!("Timer task panicked");
+            }
+            queue = self.queue.lock().unwrap();
+            if let Some(interval) = timer.interval {
+                queue.insert(due + interval, timer);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::timer::schedule_timer;
+    use std::sync::atomic::{AtomicI32, Ordering};
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn it_fires_timers_until_aborted() {
+        let once = Arc::new(AtomicI32::new(0));
+        let once_c = once.clone();
+        schedule_timer(Instant::now() + Duration::from_millis(5), None, move || {
+            once_c.fetch_add(1, Ordering::Relaxed);
+        });
+        let repeated = Arc::new(AtomicI32::new(0));
+        let repeated_c = repeated.clone();
+        let handle = schedule_timer(Instant::now(), Some(Duration::from_millis(2)), move || {
+            repeated_c.fetch_add(1, Ordering::Relaxed);
+        });
+        while repeated.load(Ordering::Relaxed) < 10 {
+            thread::sleep(Duration::from_millis(1));
+        }
+        handle.abort();
+        thread::sleep(Duration::from_millis(10));
+        let count = repeated.load(Ordering::Relaxed);
+        thread::sleep(Duration::from_millis(10));
+        assert_eq!(repeated.load(Ordering::Relaxed), count);
+        assert_eq!(once.load(Ordering::Relaxed), 1);
+    }
+}
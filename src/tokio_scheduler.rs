@@ -0,0 +1,120 @@
+use crate::scheduler::Scheduler;
+use futures::future::{AbortHandle, RemoteHandle};
+use futures::FutureExt;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::runtime::Handle;
+
+/// Runs tasks, and with them the blocking operator loops, on the runtime's blocking thread pool,
+/// so they never stall its async workers.
+impl Scheduler for Handle {
+    fn schedule<R>(&self, task: impl FnOnce() -> R + Send + 'static) -> RemoteHandle<R>
+    where
+        R: Send + 'static,
+    {
+        let (remote, remote_handle) = async { (task)() }.remote_handle();
+        self.spawn_blocking(move || remote.now_or_never());
+        remote_handle
+    }
+
+    /// Runs the task on the blocking thread pool as well, as it may block on a bounded channel.
+    /// Panics if the runtime was built without `enable_time`.
+    fn schedule_repeating<F>(&self, task: F, interval: Duration) -> AbortHandle
+    where
+        F: Fn() + Send + 'static,
+    {
+        let handle = self.clone();
+        let task = Arc::new(Mutex::new(task));
+        // Created right away, so a runtime without timers fails here instead of inside the spawned task
+        let mut interval = {
+            let _runtime = self.enter();
+            tokio::time::interval_at(tokio::time::Instant::now() + interval, interval)
+        };
+        let (repeating, abort_handle) = futures::future::abortable(async move {
+            loop {
+                interval.tick().await;
+                let task = task.clone();
+                let _ = handle
+                    .spawn_blocking(move || (task.lock().unwrap())())
+                    .await;
+            }
+        });
+        self.spawn(repeating);
+        abort_handle
+    }
+
+    /// Panics if the runtime was built without `enable_time`
+    fn schedule_after<F>(&self, delay: Duration, task: F) -> AbortHandle
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        let handle = self.clone();
+        let sleep = {
+            let _runtime = self.enter();
+            tokio::time::sleep(delay)
+        };
+        let (delayed, abort_handle) = futures::future::abortable(async move {
+            sleep.await;
+            handle.spawn_blocking(task);
+        });
+        self.spawn(delayed);
+        abort_handle
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::from_iter::from_iter;
+    use crate::observable::Observable;
+    use crate::scheduler::Scheduler;
+    use std::sync::atomic::{AtomicI32, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+    use tokio::runtime::Builder;
+
+    #[test]
+    fn it_runs_pipelines_on_tokio() {
+        let runtime = Builder::new_current_thread().build().unwrap();
+        let collector = Arc::new(Mutex::new(vec![]));
+        let collector_c = collector.clone();
+        let subscription = from_iter(0..10).map(|v| v * 2).subscribe(
+            move |v| collector_c.lock().unwrap().push(v),
+            runtime.handle().clone(),
+        );
+        runtime.block_on(subscription);
+        let mut collected = collector.lock().unwrap().clone();
+        collected.sort();
+        assert_eq!(collected, (0..10).map(|v| v * 2).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn it_repeats_on_tokio_timers() {
+        let runtime = Builder::new_current_thread().enable_time().build().unwrap();
+        let counter = Arc::new(AtomicI32::new(0));
+        let counter_c = counter.clone();
+        let handle = runtime.handle().schedule_repeating(
+            move || {
+                counter_c.fetch_add(1, Ordering::Relaxed);
+            },
+            Duration::from_millis(5),
+        );
+        runtime.block_on(async {
+            while counter.load(Ordering::Relaxed) < 3 {
+                tokio::time::sleep(Duration::from_millis(1)).await;
+            }
+        });
+        handle.abort();
+        let count = counter.load(Ordering::Relaxed);
+        runtime.block_on(async { tokio::time::sleep(Duration::from_millis(20)).await });
+        assert_eq!(counter.load(Ordering::Relaxed), count);
+    }
+
+    #[test]
+    #[should_panic(expected = "timers are disabled")]
+    fn it_fails_to_repeat_without_timers() {
+        let runtime = Builder::new_current_thread().build().unwrap();
+        runtime
+            .handle()
+            .schedule_repeating(|| {}, Duration::from_millis(5));
+    }
+}
@@ -1,21 +1,221 @@
-use crate::scheduler::Scheduler;
+use crate::deferred_scheduler::DeferredScheduler;
+use crate::notification::Notification;
+use crate::observable::Observable;
+use crate::scheduler::{LoopStep, Scheduler, Wait};
+use crate::subscriber::Subscriber;
+use crate::subscription::SubscriptionState;
+use futures::future::RemoteHandle;
 use log::trace;
-use std::sync::mpsc::{Receiver, Sender};
+use std::ops::ControlFlow;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{Receiver, RecvError, TryRecvError};
+use std::sync::{Arc, Condvar, Mutex};
 
-pub fn forward_messages<Item, O>(incoming: Receiver<Item>, outgoing: Sender<Item>, pool: O)
-where
+pub fn forward_messages<Item, E, O>(
+    incoming: Receiver<Notification<Item, E>>,
+    outgoing: Subscriber<Item, E>,
+    pool: O,
+) where
     Item: Send + 'static,
+    E: Send + 'static,
+    O: Scheduler,
+{
+    receive(
+        &pool,
+        incoming,
+        "Forwarding",
+        move |message| match message {
+            Ok(Notification::Next(message)) => {
+                if outgoing.send(Notification::Next(message)).is_err() {
+                    return ControlFlow::Break(());
+                }
+                ControlFlow::Continue(())
+            }
+            Ok(message) => {
+                let _ = outgoing.send(message);
+                ControlFlow::Break(())
+            }
+            Err(_) => ControlFlow::Break(()), // Upstream terminated without completing
+        },
+    )
+    .forget();
+}
+
+/// The end of a pipeline subscribed by `subscribe_channel`
+pub(crate) struct Subscribed<Item, E> {
+    pub(crate) incoming: Receiver<Notification<Item, E>>,
+    pub(crate) state: Arc<SubscriptionState>,
+    /// The scheduler to run the consumer on, which `observe_on` may have moved elsewhere
+    pub(crate) scheduler: DeferredScheduler,
+}
+
+/// Subscribes `source` with a subscription of its own, sending its notifications into the returned channel.
+/// A consumer scheduled afterwards is queued after the producers, so a pool with a single worker runs them first.
+pub(crate) fn subscribe_channel<S, O>(source: S, scheduler: O) -> Subscribed<S::Item, S::Error>
+where
+    S: Observable,
+    O: Scheduler + Clone + Send + 'static,
+{
+    let state = Arc::new(SubscriptionState::default());
+    let (incoming_tx, incoming_rx) = Subscriber::channel(state.clone(), source.capacity());
+    // Only resolved once the pipeline is subscribed, as `observe_on` may move the consumer elsewhere
+    let deferred = DeferredScheduler::default();
+    source.actual_subscribe(incoming_tx, deferred.clone());
+    deferred.resolve_upstream(scheduler);
+    Subscribed {
+        incoming: incoming_rx,
+        state,
+        scheduler: deferred,
+    }
+}
+
+/// Schedules `task`, which may block sending into a bounded channel, the way operator loops are scheduled,
+/// so schedulers keeping blocking loops off their workers, like Rayon's, keep it off them as well
+pub(crate) fn schedule_blocking<R, O>(
+    pool: &O,
+    task: impl FnOnce() -> R + Send + 'static,
+) -> RemoteHandle<R>
+where
+    R: Send + 'static,
+    O: Scheduler,
+{
+    let mut task = Some(task);
+    pool.schedule_loop(move |_| LoopStep::Finished((task.take().unwrap())()))
+}
+
+/// Schedules `handler` to be called with every message received on `incoming`, until it breaks
+pub(crate) fn receive<T, R, H, O>(
+    pool: &O,
+    incoming: Receiver<T>,
+    name: &'static str,
+    mut handler: H,
+) -> RemoteHandle<R>
+where
+    T: Send + 'static,
+    R: Send + 'static,
+    H: FnMut(Result<T, RecvError>) -> ControlFlow<R> + Send + 'static,
+    O: Scheduler,
+{
+    receive_with_permits(pool, incoming, None, name, move |message, _| {
+        handler(message)
+    })
+}
+
+/// Like `receive`, but only takes the next message once one of the `permits` is available,
+/// and hands it to the handler together with the message.
+pub(crate) fn receive_with_permits<T, R, H, O>(
+    pool: &O,
+    incoming: Receiver<T>,
+    permits: Option<Arc<Permits>>,
+    name: &'static str,
+    mut handler: H,
+) -> RemoteHandle<R>
+where
+    T: Send + 'static,
+    R: Send + 'static,
+    H: FnMut(Result<T, RecvError>, Option<Permit>) -> ControlFlow<R> + Send + 'static,
     O: Scheduler,
 {
-    pool.schedule(move || {
-        loop {
-            let message = incoming.recv();
-            match message {
-                Ok(message) => outgoing.send(message).unwrap(),
-                Err(_) => break, // Channel closed
+    pool.schedule_loop(move |wait| {
+        let permit = match (&permits, &wait) {
+            (None, _) => None,
+            (Some(permits), Wait::Block) => Some(permits.acquire()),
+            (Some(permits), Wait::Poll) => match permits.try_acquire() {
+                Some(permit) => Some(permit),
+                None => return LoopStep::Empty,
+            },
+        };
+        let message = match wait {
+            Wait::Block => incoming.recv(),
+            Wait::Poll => match incoming.try_recv() {
+                Ok(message) => Ok(message),
+                Err(TryRecvError::Empty) => return LoopStep::Empty,
+                Err(TryRecvError::Disconnected) => Err(RecvError),
+            },
+        };
+        match handler(message, permit) {
+            ControlFlow::Continue(()) => LoopStep::Received,
+            ControlFlow::Break(result) => {
+                trace!("{} finished", name);
+                LoopStep::Finished(result)
             }
         }
-        trace!("Forwarding finished");
     })
-    .forget();
+}
+
+/// Shared between an operator loop and the tasks it spawns per item, sends `Complete` downstream
+/// once the last clone is dropped, but only if the upstream completed.
+pub(crate) struct Completion<Item, E> {
+    channel: Subscriber<Item, E>,
+    completed: AtomicBool,
+}
+
+impl<Item, E> Completion<Item, E> {
+    pub(crate) fn new(channel: Subscriber<Item, E>) -> Self {
+        Completion {
+            channel,
+            completed: AtomicBool::new(false),
+        }
+    }
+
+    pub(crate) fn complete(&self) {
+        self.completed.store(true, Ordering::Release);
+    }
+}
+
+impl<Item, E> Drop for Completion<Item, E> {
+    fn drop(&mut self) {
+        if self.completed.load(Ordering::Acquire) {
+            let _ = self.channel.send(Notification::Complete);
+        }
+    }
+}
+
+/// Limits the number of per-item tasks an operator has in flight on a bounded pipeline
+pub(crate) struct Permits {
+    available: Mutex<usize>,
+    released: Condvar,
+}
+
+impl Permits {
+    pub(crate) fn new(count: usize) -> Self {
+        Permits {
+            available: Mutex::new(count.max(1)),
+            released: Condvar::new(),
+        }
+    }
+
+    /// Blocks until a permit is available, which is given back once the returned guard is dropped
+    pub(crate) fn acquire(self: &Arc<Self>) -> Permit {
+        let mut available = self.available.lock().unwrap();
+        while *available == 0 {
+            available = self.released.wait(available).unwrap();
+        }
+        *available -= 1;
+        Permit {
+            permits: self.clone(),
+        }
+    }
+
+    pub(crate) fn try_acquire(self: &Arc<Self>) -> Option<Permit> {
+        let mut available = self.available.lock().unwrap();
+        if *available == 0 {
+            return None;
+        }
+        *available -= 1;
+        Some(Permit {
+            permits: self.clone(),
+        })
+    }
+}
+
+pub(crate) struct Permit {
+    permits: Arc<Permits>,
+}
+
+impl Drop for Permit {
+    fn drop(&mut self) {
+        *self.permits.available.lock().unwrap() += 1;
+        self.permits.released.notify_one();
+    }
 }